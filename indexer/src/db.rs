@@ -0,0 +1,503 @@
+//! SQLite schema of the index and the rows written for each event.
+//!
+//! Balances are stored as decimal text since they do not fit SQLite integers, accounts in SS58
+//! and mint rates (liquid per staking currency) as reals. Rows of an event are keyed by block and
+//! the index of the event in the block.
+
+use crate::{
+	events::{DemocracyEvent, Event, LiquidStakingEvent, StakingEvent},
+	rpc::Events,
+	AccountId, Balance, BlockNumber, Hash, Result,
+};
+use frame_system::Phase;
+use pallet_democracy::{AccountVote, Conviction};
+use pallet_liquid_staking::MintRate;
+use rusqlite::{params, Connection};
+use sp_core::crypto::Ss58Codec;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS blocks (
+	number INTEGER PRIMARY KEY,
+	hash TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deposits (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	pool_id INTEGER NOT NULL,
+	account TEXT NOT NULL,
+	staking_amount TEXT NOT NULL,
+	liquid_amount TEXT NOT NULL,
+	rate REAL NOT NULL,
+	era INTEGER NOT NULL,
+	PRIMARY KEY (block, event)
+);
+-- kind is one of request, withdraw, cancel and emergency
+CREATE TABLE IF NOT EXISTS redemptions (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	pool_id INTEGER NOT NULL,
+	claim_id INTEGER,
+	account TEXT NOT NULL,
+	kind TEXT NOT NULL,
+	staking_amount TEXT NOT NULL,
+	liquid_amount TEXT NOT NULL,
+	rate REAL,
+	era INTEGER,
+	PRIMARY KEY (block, event)
+);
+CREATE TABLE IF NOT EXISTS votes (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	pool_id INTEGER NOT NULL,
+	voter TEXT NOT NULL,
+	validator TEXT NOT NULL,
+	liquid_amount TEXT NOT NULL,
+	PRIMARY KEY (block, event)
+);
+-- removal_reason is null for applied nominations
+CREATE TABLE IF NOT EXISTS nominations (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	pool_id INTEGER NOT NULL,
+	validator TEXT NOT NULL,
+	removal_reason TEXT,
+	PRIMARY KEY (block, event, validator)
+);
+CREATE TABLE IF NOT EXISTS rewards (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	era INTEGER,
+	stash TEXT NOT NULL,
+	amount TEXT NOT NULL,
+	PRIMARY KEY (block, event)
+);
+CREATE TABLE IF NOT EXISTS slashes (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	validator TEXT NOT NULL,
+	amount TEXT NOT NULL,
+	PRIMARY KEY (block, event)
+);
+CREATE TABLE IF NOT EXISTS pot_ledgers (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	pool_id INTEGER NOT NULL,
+	bonded TEXT NOT NULL,
+	active TEXT NOT NULL,
+	unclaimed TEXT NOT NULL,
+	PRIMARY KEY (block, event)
+);
+-- status is one of started, passed, not_passed and cancelled, threshold is set when started
+CREATE TABLE IF NOT EXISTS referenda (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	ref_index INTEGER NOT NULL,
+	status TEXT NOT NULL,
+	threshold TEXT,
+	PRIMARY KEY (block, event)
+);
+-- a standard vote puts its whole amount on one side, split votes have the conviction None
+CREATE TABLE IF NOT EXISTS referendum_votes (
+	block INTEGER NOT NULL,
+	event INTEGER NOT NULL,
+	ref_index INTEGER NOT NULL,
+	voter TEXT NOT NULL,
+	aye_amount TEXT NOT NULL,
+	nay_amount TEXT NOT NULL,
+	conviction TEXT NOT NULL,
+	PRIMARY KEY (block, event)
+);
+CREATE TABLE IF NOT EXISTS era_rates (
+	pool_id INTEGER NOT NULL,
+	era INTEGER NOT NULL,
+	rate REAL NOT NULL,
+	block INTEGER NOT NULL,
+	PRIMARY KEY (pool_id, era)
+);
+";
+
+/// Block number and index of an event in the block.
+type At = (BlockNumber, u32);
+
+pub struct Database {
+	conn: Connection,
+}
+
+impl Database {
+	pub fn open(path: &Path) -> Result<Self> {
+		Self::new(Connection::open(path)?)
+	}
+
+	fn new(conn: Connection) -> Result<Self> {
+		conn.execute_batch(SCHEMA)?;
+		Ok(Self { conn })
+	}
+
+	/// Last block written to the database.
+	pub fn last_block(&self) -> Result<Option<BlockNumber>> {
+		Ok(self.conn.query_row("SELECT MAX(number) FROM blocks", [], |row| row.get(0))?)
+	}
+
+	/// Write block `number` and the rows of its events, all or nothing.
+	pub fn insert_block(&mut self, number: BlockNumber, hash: Hash, events: &Events) -> Result<()> {
+		let tx = self.conn.transaction()?;
+		tx.execute(
+			"INSERT INTO blocks (number, hash) VALUES (?1, ?2)",
+			params![number, format!("{:?}", hash)],
+		)?;
+
+		// rewards follow the `PayoutStarted` of their era in the same extrinsic
+		let mut payout: Option<(&Phase, u32)> = None;
+		for (index, record) in events.iter().enumerate() {
+			let at = (number, index as u32);
+			match &record.event {
+				Event::LiquidStaking(event) => insert_liquid_staking(&tx, at, event)?,
+				Event::Staking(StakingEvent::PayoutStarted(era, _)) =>
+					payout = Some((&record.phase, *era)),
+				Event::Staking(StakingEvent::Rewarded(stash, amount)) => {
+					let era = payout
+						.filter(|(phase, _)| **phase == record.phase)
+						.map(|(_, era)| era);
+					tx.execute(
+						"INSERT INTO rewards VALUES (?1, ?2, ?3, ?4, ?5)",
+						params![at.0, at.1, era, account(stash), balance(amount)],
+					)?;
+				},
+				Event::Staking(StakingEvent::Slashed(validator, amount)) => {
+					tx.execute(
+						"INSERT INTO slashes VALUES (?1, ?2, ?3, ?4)",
+						params![at.0, at.1, account(validator), balance(amount)],
+					)?;
+				},
+				Event::Democracy(event) => insert_democracy(&tx, at, event)?,
+				Event::Other => {},
+			}
+		}
+		tx.commit()?;
+		Ok(())
+	}
+}
+
+fn insert_liquid_staking(
+	conn: &Connection,
+	(block, index): At,
+	event: &LiquidStakingEvent,
+) -> rusqlite::Result<()> {
+	use LiquidStakingEvent::*;
+	let redemption = "INSERT INTO redemptions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+	match event {
+		BondAndMint { pool_id, who, staking_amount, liquid_amount, rate, era } => {
+			conn.execute(
+				"INSERT INTO deposits VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+				params![
+					block,
+					index,
+					pool_id,
+					account(who),
+					balance(staking_amount),
+					balance(liquid_amount),
+					mint_rate(rate),
+					era,
+				],
+			)?;
+		},
+		RequestUnbond { pool_id, claim_id, who, liquid_amount, staking_amount, rate, era } => {
+			conn.execute(
+				redemption,
+				params![
+					block,
+					index,
+					pool_id,
+					claim_id,
+					account(who),
+					"request",
+					balance(staking_amount),
+					balance(liquid_amount),
+					mint_rate(rate),
+					era,
+				],
+			)?;
+		},
+		Withdraw { pool_id, claim_id, who, staking_amount, liquid_amount, era } => {
+			conn.execute(
+				redemption,
+				params![
+					block,
+					index,
+					pool_id,
+					claim_id,
+					account(who),
+					"withdraw",
+					balance(staking_amount),
+					balance(liquid_amount),
+					None::<f64>,
+					era,
+				],
+			)?;
+		},
+		UnbondCancelled { pool_id, claim_id, who, staking_amount, liquid_amount, rate } => {
+			conn.execute(
+				redemption,
+				params![
+					block,
+					index,
+					pool_id,
+					claim_id,
+					account(who),
+					"cancel",
+					balance(staking_amount),
+					balance(liquid_amount),
+					mint_rate(rate),
+					None::<u32>,
+				],
+			)?;
+		},
+		EmergencyRedeemed { pool_id, who, liquid_amount, staking_amount } => {
+			conn.execute(
+				redemption,
+				params![
+					block,
+					index,
+					pool_id,
+					None::<u32>,
+					account(who),
+					"emergency",
+					balance(staking_amount),
+					balance(liquid_amount),
+					None::<f64>,
+					None::<u32>,
+				],
+			)?;
+		},
+		Voted { pool_id, voter, validator, liquid_amount } => {
+			conn.execute(
+				"INSERT INTO votes VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+				params![
+					block,
+					index,
+					pool_id,
+					account(voter),
+					account(validator),
+					balance(liquid_amount),
+				],
+			)?;
+		},
+		NominationsApplied { pool_id, validators } =>
+			for validator in validators {
+				conn.execute(
+					"INSERT INTO nominations VALUES (?1, ?2, ?3, ?4, NULL)",
+					params![block, index, pool_id, account(validator)],
+				)?;
+			},
+		NominationRemoved { pool_id, validator, reason } => {
+			conn.execute(
+				"INSERT INTO nominations VALUES (?1, ?2, ?3, ?4, ?5)",
+				params![block, index, pool_id, account(validator), format!("{:?}", reason)],
+			)?;
+		},
+		PotLedgerUpdated { pool_id, bonded, active, unclaimed } => {
+			conn.execute(
+				"INSERT INTO pot_ledgers VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+				params![
+					block,
+					index,
+					pool_id,
+					balance(bonded),
+					balance(active),
+					balance(unclaimed),
+				],
+			)?;
+		},
+		MintRateRecorded { pool_id, era, rate } => {
+			conn.execute(
+				"INSERT INTO era_rates VALUES (?1, ?2, ?3, ?4)",
+				params![pool_id, era, mint_rate(rate), block],
+			)?;
+		},
+	}
+	Ok(())
+}
+
+fn insert_democracy(
+	conn: &Connection,
+	(block, index): At,
+	event: &DemocracyEvent,
+) -> rusqlite::Result<()> {
+	use DemocracyEvent::*;
+	let referendum = "INSERT INTO referenda VALUES (?1, ?2, ?3, ?4, ?5)";
+	match event {
+		Started { ref_index, threshold } => {
+			conn.execute(
+				referendum,
+				params![block, index, ref_index, "started", format!("{:?}", threshold)],
+			)?;
+		},
+		Passed { ref_index } => {
+			conn.execute(referendum, params![block, index, ref_index, "passed", None::<String>])?;
+		},
+		NotPassed { ref_index } => {
+			conn.execute(
+				referendum,
+				params![block, index, ref_index, "not_passed", None::<String>],
+			)?;
+		},
+		Cancelled { ref_index } => {
+			conn.execute(
+				referendum,
+				params![block, index, ref_index, "cancelled", None::<String>],
+			)?;
+		},
+		Voted { voter, ref_index, vote } => {
+			let (aye, nay, conviction) = match vote {
+				AccountVote::Standard { vote, balance } if vote.aye =>
+					(*balance, 0, vote.conviction),
+				AccountVote::Standard { vote, balance } => (0, *balance, vote.conviction),
+				AccountVote::Split { aye, nay } => (*aye, *nay, Conviction::None),
+			};
+			conn.execute(
+				"INSERT INTO referendum_votes VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+				params![
+					block,
+					index,
+					ref_index,
+					account(voter),
+					balance(&aye),
+					balance(&nay),
+					format!("{:?}", conviction),
+				],
+			)?;
+		},
+	}
+	Ok(())
+}
+
+fn account(account: &AccountId) -> String {
+	account.to_ss58check()
+}
+
+fn balance(balance: &Balance) -> String {
+	balance.to_string()
+}
+
+fn mint_rate(rate: &MintRate) -> f64 {
+	rate.to_float()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::events::EventRecord;
+	use pallet_democracy::{Vote, VoteThreshold};
+	use sp_runtime::FixedPointNumber;
+
+	fn record(phase: u32, event: Event) -> EventRecord {
+		EventRecord { phase: Phase::ApplyExtrinsic(phase), event }
+	}
+
+	fn count(db: &Database, table: &str) -> u32 {
+		db.conn
+			.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0))
+			.unwrap()
+	}
+
+	#[test]
+	fn events_are_written_per_block() {
+		let mut db = Database::new(Connection::open_in_memory().unwrap()).unwrap();
+		assert_eq!(db.last_block().unwrap(), None);
+		let who = AccountId::new([1; 32]);
+		let validator = AccountId::new([2; 32]);
+
+		let events = vec![
+			record(
+				0,
+				Event::LiquidStaking(LiquidStakingEvent::BondAndMint {
+					pool_id: 0,
+					who: who.clone(),
+					staking_amount: 100,
+					liquid_amount: 1000,
+					rate: MintRate::saturating_from_integer(10),
+					era: 3,
+				}),
+			),
+			record(
+				1,
+				Event::LiquidStaking(LiquidStakingEvent::Withdraw {
+					pool_id: 0,
+					claim_id: 7,
+					who: who.clone(),
+					staking_amount: 50,
+					liquid_amount: 500,
+					era: 4,
+				}),
+			),
+			record(2, Event::Staking(StakingEvent::PayoutStarted(2, validator.clone()))),
+			record(2, Event::Staking(StakingEvent::Rewarded(validator, 20))),
+			record(
+				3,
+				Event::LiquidStaking(LiquidStakingEvent::MintRateRecorded {
+					pool_id: 0,
+					era: 4,
+					rate: MintRate::saturating_from_integer(10),
+				}),
+			),
+			record(3, Event::Other),
+			record(
+				4,
+				Event::Democracy(DemocracyEvent::Started {
+					ref_index: 0,
+					threshold: VoteThreshold::SimpleMajority,
+				}),
+			),
+			record(
+				5,
+				Event::Democracy(DemocracyEvent::Voted {
+					voter: who.clone(),
+					ref_index: 0,
+					vote: AccountVote::Standard {
+						vote: Vote { aye: false, conviction: Conviction::Locked2x },
+						balance: 30,
+					},
+				}),
+			),
+		];
+		db.insert_block(5, Hash::repeat_byte(1), &events).unwrap();
+		assert_eq!(db.last_block().unwrap(), Some(5));
+
+		let deposit: (String, String, f64, u32) = db
+			.conn
+			.query_row(
+				"SELECT account, liquid_amount, rate, era FROM deposits WHERE block = 5",
+				[],
+				|row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+			)
+			.unwrap();
+		assert_eq!(deposit, (who.to_ss58check(), "1000".into(), 10.0, 3));
+		let redemption: (String, Option<u32>) = db
+			.conn
+			.query_row("SELECT kind, claim_id FROM redemptions", [], |row| {
+				Ok((row.get(0)?, row.get(1)?))
+			})
+			.unwrap();
+		assert_eq!(redemption, ("withdraw".into(), Some(7)));
+		// the reward is attributed to the era being paid out
+		let era: Option<u32> =
+			db.conn.query_row("SELECT era FROM rewards", [], |row| row.get(0)).unwrap();
+		assert_eq!(era, Some(2));
+		assert_eq!(count(&db, "slashes"), 0);
+		assert_eq!(count(&db, "era_rates"), 1);
+		assert_eq!(count(&db, "referenda"), 1);
+		let vote: (String, String, String) = db
+			.conn
+			.query_row(
+				"SELECT aye_amount, nay_amount, conviction FROM referendum_votes",
+				[],
+				|row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+			)
+			.unwrap();
+		assert_eq!(vote, ("0".into(), "30".into(), "Locked2x".into()));
+
+		// a block is only written once
+		assert!(db.insert_block(5, Hash::repeat_byte(1), &Vec::new()).is_err());
+	}
+}
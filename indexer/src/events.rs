@@ -0,0 +1,491 @@
+//! Events of a block, decoded with the metadata of the runtime instead of its types so the indexer
+//! does not link the runtime.
+//!
+//! The events the indexer writes are decoded by the name of their pallet and variant, after
+//! checking their fields against the metadata. Every other event is skipped using the type
+//! registry of the metadata.
+
+use crate::{AccountId, Balance, EraIndex, Result};
+use codec::{Compact, Decode, Input};
+use frame_metadata::{RuntimeMetadata, RuntimeMetadataPrefixed};
+use frame_system::Phase;
+use pallet_democracy::{AccountVote, ReferendumIndex, VoteThreshold};
+use pallet_liquid_staking::{ClaimId, MintRate, PoolId, RemovalReason};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, TypeDefPrimitive, Variant};
+use sp_core::H256;
+use std::collections::BTreeMap;
+
+/// Names of the indexed pallets in the runtime.
+const LIQUID_STAKING: &str = "LiquidStakingModule";
+const STAKING: &str = "Staking";
+const DEMOCRACY: &str = "Democracy";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventRecord {
+	pub phase: Phase,
+	pub event: Event,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+	LiquidStaking(LiquidStakingEvent),
+	Staking(StakingEvent),
+	Democracy(DemocracyEvent),
+	/// An event the indexer does not write.
+	Other,
+}
+
+/// The events of `pallet_liquid_staking` the indexer writes, with the fields of the pallet.
+#[derive(Clone, Debug, PartialEq)]
+pub enum LiquidStakingEvent {
+	BondAndMint {
+		pool_id: PoolId,
+		who: AccountId,
+		staking_amount: Balance,
+		liquid_amount: Balance,
+		rate: MintRate,
+		era: EraIndex,
+	},
+	RequestUnbond {
+		pool_id: PoolId,
+		claim_id: ClaimId,
+		who: AccountId,
+		liquid_amount: Balance,
+		staking_amount: Balance,
+		rate: MintRate,
+		era: EraIndex,
+	},
+	Withdraw {
+		pool_id: PoolId,
+		claim_id: ClaimId,
+		who: AccountId,
+		staking_amount: Balance,
+		liquid_amount: Balance,
+		era: EraIndex,
+	},
+	UnbondCancelled {
+		pool_id: PoolId,
+		claim_id: ClaimId,
+		who: AccountId,
+		staking_amount: Balance,
+		liquid_amount: Balance,
+		rate: MintRate,
+	},
+	EmergencyRedeemed {
+		pool_id: PoolId,
+		who: AccountId,
+		liquid_amount: Balance,
+		staking_amount: Balance,
+	},
+	Voted {
+		pool_id: PoolId,
+		voter: AccountId,
+		validator: AccountId,
+		liquid_amount: Balance,
+	},
+	NominationsApplied {
+		pool_id: PoolId,
+		validators: Vec<AccountId>,
+	},
+	NominationRemoved {
+		pool_id: PoolId,
+		validator: AccountId,
+		reason: RemovalReason,
+	},
+	PotLedgerUpdated {
+		pool_id: PoolId,
+		bonded: Balance,
+		active: Balance,
+		unclaimed: Balance,
+	},
+	MintRateRecorded {
+		pool_id: PoolId,
+		era: EraIndex,
+		rate: MintRate,
+	},
+}
+
+/// The events of `pallet_staking` the indexer writes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum StakingEvent {
+	PayoutStarted(EraIndex, AccountId),
+	Rewarded(AccountId, Balance),
+	Slashed(AccountId, Balance),
+}
+
+/// The events of `pallet_democracy` the indexer writes: the referenda and their votes.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DemocracyEvent {
+	Started { ref_index: ReferendumIndex, threshold: VoteThreshold },
+	Passed { ref_index: ReferendumIndex },
+	NotPassed { ref_index: ReferendumIndex },
+	Cancelled { ref_index: ReferendumIndex },
+	Voted { voter: AccountId, ref_index: ReferendumIndex, vote: AccountVote<Balance> },
+}
+
+/// Decode the fields of the named variants of `$event`, in the order they are listed, or
+/// evaluate to `None` for any other variant.
+macro_rules! decode_named {
+	($variant:ident, $input:ident, $event:ident {
+		$($name:ident { $($field:ident: $ty:ty),* $(,)? }),* $(,)?
+	}) => {
+		match $variant.name().as_str() {
+			$(stringify!($name) => {
+				check_fields($variant, &[$(stringify!($field)),*])?;
+				Some($event::$name { $($field: <$ty>::decode($input)?),* })
+			},)*
+			_ => None,
+		}
+	};
+}
+
+/// The part of the runtime metadata needed to decode events.
+pub struct Metadata {
+	types: PortableRegistry,
+	/// Name and event type of each pallet by index, which is the index of its events in the
+	/// event of the runtime.
+	pallets: BTreeMap<u8, (String, Option<u32>)>,
+}
+
+impl Metadata {
+	fn new(types: PortableRegistry, pallets: BTreeMap<u8, (String, Option<u32>)>) -> Self {
+		Self { types, pallets }
+	}
+
+	/// Decode the metadata returned by `state_getMetadata`.
+	pub fn decode(encoded: &[u8]) -> Result<Self> {
+		let metadata = match RuntimeMetadataPrefixed::decode(&mut &encoded[..])?.1 {
+			RuntimeMetadata::V14(metadata) => metadata,
+			_ => return Err("only metadata v14 is supported".into()),
+		};
+		let pallets = metadata
+			.pallets
+			.iter()
+			.map(|pallet| {
+				(pallet.index, (pallet.name.clone(), pallet.event.as_ref().map(|e| e.ty.id())))
+			})
+			.collect();
+		Ok(Self::new(metadata.types, pallets))
+	}
+
+	/// Decode the value of `System::Events`.
+	pub fn events(&self, encoded: &[u8]) -> Result<Vec<EventRecord>> {
+		let input = &mut &encoded[..];
+		let count = Compact::<u32>::decode(input)?.0;
+		let events = (0..count).map(|_| self.record(input)).collect::<Result<Vec<_>>>()?;
+		if !input.is_empty() {
+			return Err(format!("{} bytes left after the events", input.len()).into())
+		}
+		Ok(events)
+	}
+
+	fn record(&self, input: &mut &[u8]) -> Result<EventRecord> {
+		let phase = Phase::decode(input)?;
+		let event = self.event(input)?;
+		let _topics = Vec::<H256>::decode(input)?;
+		Ok(EventRecord { phase, event })
+	}
+
+	fn event(&self, input: &mut &[u8]) -> Result<Event> {
+		let pallet_index = input.read_byte()?;
+		let (pallet, ty) = self
+			.pallets
+			.get(&pallet_index)
+			.ok_or_else(|| format!("no pallet with index {}", pallet_index))?;
+		let ty = (*ty).ok_or_else(|| format!("pallet {} has no events", pallet))?;
+		let variant = variant(&self.types, ty, input)?;
+
+		let event = match pallet.as_str() {
+			LIQUID_STAKING => decode_liquid_staking(variant, input)?.map(Event::LiquidStaking),
+			STAKING => decode_staking(variant, input)?.map(Event::Staking),
+			DEMOCRACY => decode_democracy(variant, input)?.map(Event::Democracy),
+			_ => None,
+		};
+		match event {
+			Some(event) => Ok(event),
+			None => {
+				for field in variant.fields() {
+					skip(&self.types, field.ty().id(), input)?;
+				}
+				Ok(Event::Other)
+			},
+		}
+	}
+}
+
+fn decode_liquid_staking(
+	variant: &Variant<PortableForm>,
+	input: &mut &[u8],
+) -> Result<Option<LiquidStakingEvent>> {
+	Ok(decode_named!(variant, input, LiquidStakingEvent {
+		BondAndMint {
+			pool_id: PoolId,
+			who: AccountId,
+			staking_amount: Balance,
+			liquid_amount: Balance,
+			rate: MintRate,
+			era: EraIndex,
+		},
+		RequestUnbond {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			who: AccountId,
+			liquid_amount: Balance,
+			staking_amount: Balance,
+			rate: MintRate,
+			era: EraIndex,
+		},
+		Withdraw {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			who: AccountId,
+			staking_amount: Balance,
+			liquid_amount: Balance,
+			era: EraIndex,
+		},
+		UnbondCancelled {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			who: AccountId,
+			staking_amount: Balance,
+			liquid_amount: Balance,
+			rate: MintRate,
+		},
+		EmergencyRedeemed {
+			pool_id: PoolId,
+			who: AccountId,
+			liquid_amount: Balance,
+			staking_amount: Balance,
+		},
+		Voted { pool_id: PoolId, voter: AccountId, validator: AccountId, liquid_amount: Balance },
+		NominationsApplied { pool_id: PoolId, validators: Vec<AccountId> },
+		NominationRemoved { pool_id: PoolId, validator: AccountId, reason: RemovalReason },
+		PotLedgerUpdated { pool_id: PoolId, bonded: Balance, active: Balance, unclaimed: Balance },
+		MintRateRecorded { pool_id: PoolId, era: EraIndex, rate: MintRate },
+	}))
+}
+
+fn decode_staking(
+	variant: &Variant<PortableForm>,
+	input: &mut &[u8],
+) -> Result<Option<StakingEvent>> {
+	// the fields of the staking events are not named, only their number is checked
+	let event = match variant.name().as_str() {
+		"PayoutStarted" => {
+			check_fields(variant, &["era", "validator"])?;
+			StakingEvent::PayoutStarted(Decode::decode(input)?, Decode::decode(input)?)
+		},
+		"Rewarded" => {
+			check_fields(variant, &["stash", "amount"])?;
+			StakingEvent::Rewarded(Decode::decode(input)?, Decode::decode(input)?)
+		},
+		"Slashed" => {
+			check_fields(variant, &["validator", "amount"])?;
+			StakingEvent::Slashed(Decode::decode(input)?, Decode::decode(input)?)
+		},
+		_ => return Ok(None),
+	};
+	Ok(Some(event))
+}
+
+fn decode_democracy(
+	variant: &Variant<PortableForm>,
+	input: &mut &[u8],
+) -> Result<Option<DemocracyEvent>> {
+	Ok(decode_named!(variant, input, DemocracyEvent {
+		Started { ref_index: ReferendumIndex, threshold: VoteThreshold },
+		Passed { ref_index: ReferendumIndex },
+		NotPassed { ref_index: ReferendumIndex },
+		Cancelled { ref_index: ReferendumIndex },
+		Voted { voter: AccountId, ref_index: ReferendumIndex, vote: AccountVote<Balance> },
+	}))
+}
+
+/// Make sure the runtime's `variant` has as many fields as the indexer decodes, with the same
+/// names where the runtime names them.
+fn check_fields(variant: &Variant<PortableForm>, names: &[&str]) -> Result<()> {
+	let fields = variant.fields();
+	let matches = fields.len() == names.len() &&
+		fields.iter().zip(names).all(|(field, name)| field.name().map_or(true, |f| f == name));
+	if !matches {
+		return Err(format!("event {} does not have the fields {:?}", variant.name(), names).into())
+	}
+	Ok(())
+}
+
+/// Read the index of a variant of enum `ty` and look it up.
+fn variant<'a>(
+	types: &'a PortableRegistry,
+	ty: u32,
+	input: &mut &[u8],
+) -> Result<&'a Variant<PortableForm>> {
+	let variants = match types.resolve(ty).map(|ty| ty.type_def()) {
+		Some(TypeDef::Variant(variants)) => variants.variants(),
+		_ => return Err(format!("type {} is not an enum", ty).into()),
+	};
+	let index = input.read_byte()?;
+	Ok(variants
+		.iter()
+		.find(|variant| variant.index() == index)
+		.ok_or_else(|| format!("type {} has no variant {}", ty, index))?)
+}
+
+/// Skip a value of type `ty`.
+fn skip(types: &PortableRegistry, ty: u32, input: &mut &[u8]) -> Result<()> {
+	let type_def =
+		types.resolve(ty).ok_or_else(|| format!("type {} is not in the metadata", ty))?.type_def();
+	match type_def {
+		TypeDef::Composite(composite) =>
+			for field in composite.fields() {
+				skip(types, field.ty().id(), input)?;
+			},
+		TypeDef::Variant(_) =>
+			for field in variant(types, ty, input)?.fields() {
+				skip(types, field.ty().id(), input)?;
+			},
+		TypeDef::Sequence(sequence) =>
+			for _ in 0..Compact::<u32>::decode(input)?.0 {
+				skip(types, sequence.type_param().id(), input)?;
+			},
+		TypeDef::Array(array) =>
+			for _ in 0..array.len() {
+				skip(types, array.type_param().id(), input)?;
+			},
+		TypeDef::Tuple(tuple) =>
+			for field in tuple.fields() {
+				skip(types, field.id(), input)?;
+			},
+		TypeDef::Primitive(primitive) => {
+			use TypeDefPrimitive::*;
+			let len = match primitive {
+				Bool | U8 | I8 => 1,
+				U16 | I16 => 2,
+				Char | U32 | I32 => 4,
+				U64 | I64 => 8,
+				U128 | I128 => 16,
+				U256 | I256 => 32,
+				Str => Compact::<u32>::decode(input)?.0 as usize,
+			};
+			if input.len() < len {
+				return Err("events end in the middle of a value".into())
+			}
+			*input = &input[len..];
+		},
+		TypeDef::Compact(_) => Compact::<u128>::skip(input)?,
+		TypeDef::BitSequence(_) => return Err("bit sequences are not supported".into()),
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use scale_info::{meta_type, Registry, TypeInfo};
+	use sp_runtime::FixedPointNumber;
+
+	#[derive(Encode, TypeInfo)]
+	enum OtherEvent {
+		Noted(Vec<u8>, (u16, bool)),
+		Moved { from: [u8; 32], amount: Compact<u128>, memo: Option<String> },
+	}
+
+	#[derive(Encode, TypeInfo)]
+	enum PalletEvent {
+		PoolCreated { pool_id: PoolId, liquid_currency_id: u32 },
+		MintRateRecorded { pool_id: PoolId, era: EraIndex, rate: MintRate },
+	}
+
+	#[derive(Encode, TypeInfo)]
+	enum DemocracyPalletEvent {
+		Voted { voter: AccountId, ref_index: ReferendumIndex, vote: AccountVote<Balance> },
+		Passed { ref_index: ReferendumIndex },
+	}
+
+	fn record(pallet_index: u8, event: impl Encode) -> Vec<u8> {
+		let mut encoded = Phase::ApplyExtrinsic(1).encode();
+		encoded.push(pallet_index);
+		event.encode_to(&mut encoded);
+		vec![H256::repeat_byte(1)].encode_to(&mut encoded);
+		encoded
+	}
+
+	#[test]
+	fn events_are_decoded_with_the_metadata() {
+		let mut registry = Registry::new();
+		let other = registry.register_type(&meta_type::<OtherEvent>()).id();
+		let pallet = registry.register_type(&meta_type::<PalletEvent>()).id();
+		let pallets = BTreeMap::from([
+			(0, ("System".to_string(), None)),
+			(3, ("Other".to_string(), Some(other))),
+			(7, (LIQUID_STAKING.to_string(), Some(pallet))),
+		]);
+		let metadata = Metadata::new(registry.into(), pallets);
+
+		let rate = MintRate::saturating_from_integer(10);
+		let mut encoded = Compact(4u32).encode();
+		encoded.extend(record(3, OtherEvent::Noted(vec![1, 2, 3], (4, true))));
+		encoded.extend(record(
+			3,
+			OtherEvent::Moved { from: [2; 32], amount: Compact(1_000), memo: Some("hi".into()) },
+		));
+		encoded.extend(record(7, PalletEvent::PoolCreated { pool_id: 1, liquid_currency_id: 3 }));
+		encoded.extend(record(7, PalletEvent::MintRateRecorded { pool_id: 1, era: 5, rate }));
+
+		let events = metadata
+			.events(&encoded)
+			.unwrap()
+			.into_iter()
+			.map(|record| record.event)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			events,
+			vec![
+				Event::Other,
+				Event::Other,
+				Event::Other,
+				Event::LiquidStaking(LiquidStakingEvent::MintRateRecorded {
+					pool_id: 1,
+					era: 5,
+					rate
+				}),
+			]
+		);
+
+		// events of pallets missing from the metadata do not decode
+		let mut encoded = Compact(1u32).encode();
+		encoded.extend(record(9, PalletEvent::PoolCreated { pool_id: 1, liquid_currency_id: 3 }));
+		assert!(metadata.events(&encoded).is_err());
+	}
+
+	#[test]
+	fn referendum_votes_are_decoded() {
+		let mut registry = Registry::new();
+		let democracy = registry.register_type(&meta_type::<DemocracyPalletEvent>()).id();
+		let pallets = BTreeMap::from([(4, (DEMOCRACY.to_string(), Some(democracy)))]);
+		let metadata = Metadata::new(registry.into(), pallets);
+
+		let voter = AccountId::new([1; 32]);
+		let vote = AccountVote::Split { aye: 10, nay: 20 };
+		let mut encoded = Compact(2u32).encode();
+		encoded.extend(record(
+			4,
+			DemocracyPalletEvent::Voted { voter: voter.clone(), ref_index: 3, vote },
+		));
+		encoded.extend(record(4, DemocracyPalletEvent::Passed { ref_index: 3 }));
+
+		let events = metadata
+			.events(&encoded)
+			.unwrap()
+			.into_iter()
+			.map(|record| record.event)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			events,
+			vec![
+				Event::Democracy(DemocracyEvent::Voted { voter, ref_index: 3, vote }),
+				Event::Democracy(DemocracyEvent::Passed { ref_index: 3 }),
+			]
+		);
+	}
+}
@@ -0,0 +1,75 @@
+//! Indexing of the blocks of a file written by `export-blocks`.
+//!
+//! Exported blocks carry their extrinsics but not the events these deposited, so the blocks are
+//! executed again: the node binary imports the file into a scratch database, then serves it over
+//! RPC, without peers and without authoring, while the indexer reads the events of each block.
+
+use crate::{rpc::Node, Result};
+use std::{
+	path::{Path, PathBuf},
+	process::{Child, Command, Stdio},
+	time::Duration,
+};
+
+/// Seconds the node serving the imported blocks is given to start.
+const STARTUP: u64 = 60;
+
+/// A node serving the blocks imported from a file, stopped and removed when dropped.
+pub struct ImportedChain {
+	node: Child,
+	base_path: PathBuf,
+	url: String,
+}
+
+impl ImportedChain {
+	/// Import the blocks of `file`, exported with `--binary` if `binary`, with the `node` binary
+	/// for `chain`, and serve them on `port`.
+	pub fn start(node: &Path, chain: &str, file: &Path, binary: bool, port: u16) -> Result<Self> {
+		let base_path =
+			std::env::temp_dir().join(format!("stayquid-indexer-{}", std::process::id()));
+		// the state of every block is kept to read its events
+		let mut import = Command::new(node);
+		import.args(["import-blocks", "--chain", chain, "--pruning", "archive", "--base-path"]);
+		import.arg(&base_path);
+		if binary {
+			import.arg("--binary");
+		}
+		let status = import.arg(file).status()?;
+		if !status.success() {
+			let _ = std::fs::remove_dir_all(&base_path);
+			let error = format!("{} could not import {}", node.display(), file.display());
+			return Err(format!("{}: {}", error, status).into())
+		}
+		log::info!("imported {} into {}", file.display(), base_path.display());
+
+		let node = Command::new(node)
+			.args(["--chain", chain, "--pruning", "archive", "--base-path"])
+			.arg(&base_path)
+			.args(["--ws-port", &port.to_string(), "--rpc-port", &(port + 1).to_string()])
+			.args(["--port", "0", "--reserved-only", "--no-mdns"])
+			.args(["--no-telemetry", "--no-prometheus"])
+			.stdout(Stdio::null())
+			.stderr(Stdio::null())
+			.spawn()?;
+		Ok(Self { node, base_path, url: format!("ws://127.0.0.1:{}", port) })
+	}
+
+	/// Connect to the node serving the imported blocks once it is up.
+	pub async fn connect(&self) -> Result<Node> {
+		for _ in 0..STARTUP {
+			if let Ok(node) = Node::connect(&self.url).await {
+				return Ok(node)
+			}
+			tokio::time::sleep(Duration::from_secs(1)).await;
+		}
+		Node::connect(&self.url).await
+	}
+}
+
+impl Drop for ImportedChain {
+	fn drop(&mut self) {
+		let _ = self.node.kill();
+		let _ = self.node.wait();
+		let _ = std::fs::remove_dir_all(&self.base_path);
+	}
+}
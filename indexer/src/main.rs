@@ -0,0 +1,120 @@
+//! Follows the finalized blocks of a node and writes the history of liquid staking to a SQLite
+//! database: deposits, redemptions, validator votes, nominations, rewards, slashes, pot ledgers
+//! and the mint rate of every pool per era.
+//!
+//! Indexing resumes after the last block in the database, so the indexer can be stopped and
+//! restarted at any time. Blocks exported with `export-blocks` are indexed by executing them
+//! again with the node binary, see `import`.
+//!
+//! Events are decoded with the metadata the node serves, so the indexer does not depend on the
+//! runtime. The types below must match the runtime's.
+
+mod db;
+mod events;
+mod import;
+mod rpc;
+
+use clap::Parser;
+use sp_runtime::traits::BlakeTwo256;
+use std::{path::PathBuf, time::Duration};
+
+pub type BlockNumber = u32;
+pub type Hash = sp_core::H256;
+pub type Header = sp_runtime::generic::Header<BlockNumber, BlakeTwo256>;
+pub type AccountId = sp_runtime::AccountId32;
+pub type Balance = u128;
+pub type EraIndex = u32;
+
+pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+#[derive(Debug, Parser)]
+#[clap(about = "Index the liquid staking history of a node into SQLite")]
+struct Cli {
+	/// WebSocket RPC endpoint of the node.
+	#[clap(long, default_value = "ws://127.0.0.1:9944")]
+	url: String,
+
+	/// SQLite database to write to, created if missing.
+	#[clap(long, default_value = "stayquid.db")]
+	db: PathBuf,
+
+	/// First block to index if the database is empty.
+	#[clap(long, default_value_t = 0)]
+	from: BlockNumber,
+
+	/// Stop after this block instead of following the finalized head.
+	#[clap(long)]
+	to: Option<BlockNumber>,
+
+	/// Seconds to wait before looking for newly finalized blocks.
+	#[clap(long, default_value_t = 6)]
+	poll: u64,
+
+	/// Index the blocks of a file written by `export-blocks` instead of following `url`.
+	#[clap(long)]
+	import: Option<PathBuf>,
+
+	/// The file to import was exported with `--binary`.
+	#[clap(long, requires = "import")]
+	binary: bool,
+
+	/// Node binary executing the imported blocks.
+	#[clap(long, default_value = "node-template")]
+	node: PathBuf,
+
+	/// Chain of the imported blocks, as passed to the node.
+	#[clap(long, default_value = "dev")]
+	chain: String,
+
+	/// WebSocket port of the node serving the imported blocks, its HTTP RPC takes the next one.
+	#[clap(long, default_value_t = 9955)]
+	import_port: u16,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+	env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+	let cli = Cli::parse();
+
+	let mut db = db::Database::open(&cli.db)?;
+	let imported = match &cli.import {
+		Some(file) => Some(import::ImportedChain::start(
+			&cli.node,
+			&cli.chain,
+			file,
+			cli.binary,
+			cli.import_port,
+		)?),
+		None => None,
+	};
+	let mut node = match &imported {
+		Some(imported) => imported.connect().await?,
+		None => rpc::Node::connect(&cli.url).await?,
+	};
+	let source = cli.import.as_ref().map_or(cli.url.clone(), |file| file.display().to_string());
+	let mut next = db.last_block()?.map_or(cli.from, |last| last + 1);
+	log::info!("indexing {} into {} from block {}", source, cli.db.display(), next);
+
+	loop {
+		// imported blocks are not finalized, and no more are coming
+		let head = match imported {
+			Some(_) => node.best_number().await?,
+			None => node.finalized_number().await?,
+		};
+		let last = cli.to.map_or(head, |to| to.min(head));
+		while next <= last {
+			let (hash, events) = node.events(next).await?;
+			db.insert_block(next, hash, &events)?;
+			if next % 1000 == 0 {
+				log::info!("indexed block {}", next);
+			}
+			next += 1;
+		}
+
+		if imported.is_some() || cli.to.map_or(false, |to| next > to) {
+			log::info!("indexed up to block {}", next.saturating_sub(1));
+			return Ok(())
+		}
+		tokio::time::sleep(Duration::from_secs(cli.poll)).await;
+	}
+}
@@ -0,0 +1,89 @@
+//! The few node RPCs the indexer needs.
+
+use crate::{
+	events::{EventRecord, Metadata},
+	BlockNumber, Hash, Header, Result,
+};
+use frame_support::storage::storage_prefix;
+use jsonrpsee::{
+	core::client::ClientT,
+	rpc_params,
+	ws_client::{WsClient, WsClientBuilder},
+};
+use serde::Deserialize;
+use sp_core::{
+	storage::{StorageData, StorageKey},
+	Bytes,
+};
+
+/// Events of a block, in the order they were deposited.
+pub type Events = Vec<EventRecord>;
+
+/// The part of the runtime version the indexer reads.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuntimeVersion {
+	spec_version: u32,
+}
+
+pub struct Node {
+	client: WsClient,
+	/// Metadata of the last runtime seen and its spec version.
+	metadata: Option<(u32, Metadata)>,
+}
+
+impl Node {
+	pub async fn connect(url: &str) -> Result<Self> {
+		Ok(Self { client: WsClientBuilder::default().build(url).await?, metadata: None })
+	}
+
+	/// Number of the last finalized block.
+	pub async fn finalized_number(&self) -> Result<BlockNumber> {
+		let hash: Hash = self.client.request("chain_getFinalizedHead", None).await?;
+		let header: Option<Header> =
+			self.client.request("chain_getHeader", rpc_params![hash]).await?;
+		Ok(header.ok_or_else(|| format!("finalized block {} not found", hash))?.number)
+	}
+
+	/// Number of the best block.
+	pub async fn best_number(&self) -> Result<BlockNumber> {
+		let header: Option<Header> = self.client.request("chain_getHeader", None).await?;
+		Ok(header.ok_or("the node has no best block")?.number)
+	}
+
+	/// Hash and events of block `number`.
+	pub async fn events(&mut self, number: BlockNumber) -> Result<(Hash, Events)> {
+		let hash: Option<Hash> =
+			self.client.request("chain_getBlockHash", rpc_params![number]).await?;
+		let hash = hash.ok_or_else(|| format!("block {} not found", number))?;
+
+		let key = StorageKey(storage_prefix(b"System", b"Events").to_vec());
+		let events: Option<StorageData> =
+			self.client.request("state_getStorage", rpc_params![key, hash]).await?;
+		let events = match events {
+			Some(StorageData(encoded)) => self
+				.metadata(hash)
+				.await?
+				.events(&encoded)
+				.map_err(|e| format!("events of block {} do not decode: {}", number, e))?,
+			None => Vec::new(),
+		};
+		Ok((hash, events))
+	}
+
+	/// Metadata of the runtime of block `hash`, fetched again when the runtime is upgraded.
+	async fn metadata(&mut self, hash: Hash) -> Result<&Metadata> {
+		let version: RuntimeVersion =
+			self.client.request("state_getRuntimeVersion", rpc_params![hash]).await?;
+		let outdated = self.metadata.as_ref().map_or(true, |(spec_version, _)| {
+			*spec_version != version.spec_version
+		});
+		if outdated {
+			let encoded: Bytes =
+				self.client.request("state_getMetadata", rpc_params![hash]).await?;
+			log::info!("read the metadata of runtime version {}", version.spec_version);
+			self.metadata = Some((version.spec_version, Metadata::decode(&encoded)?));
+		}
+		Ok(self.metadata.as_ref().map(|(_, metadata)| metadata).expect("set above; qed"))
+	}
+}
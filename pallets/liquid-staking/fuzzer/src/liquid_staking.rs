@@ -0,0 +1,165 @@
+//! # Running
+//! Running this fuzzer can be done with `cargo hfuzz run liquid-staking`. `honggfuzz` CLI options
+//! can be used by setting `HFUZZ_RUN_ARGS`, such as `-n 4` to use 4 threads.
+//!
+//! # Debugging a panic
+//! Once a panic is found, it can be debugged with
+//! `cargo hfuzz run-debug liquid-staking hfuzz_workspace/liquid-staking/*.fuzz`.
+//!
+//! # More information
+//! More information about `honggfuzz` can be found
+//! [here](https://docs.rs/honggfuzz/).
+//!
+//! Each input is a sequence of bonds, votes, unbonds, withdrawals, era advances, rewards and
+//! slashes run against the mock runtime of the pallet. After every step the pools must pass
+//! `sanity_check`, and the staking currency backing each unit of liquid currency must not go down
+//! unless a slash happened.
+
+use arbitrary::Arbitrary;
+use frame_support::traits::Hooks;
+use honggfuzz::fuzz;
+use pallet_liquid_staking::mock::*;
+use primitives::LIQUID_CURRENCY_ID;
+use sp_runtime::Perbill;
+
+/// Steps run for a single input at most.
+const MAX_STEPS: usize = 128;
+
+#[derive(Debug, Arbitrary)]
+enum Action {
+	Bond { who: u8, amount: u16 },
+	Unbond { who: u8, amount: u16 },
+	Withdraw { claim_id: u8 },
+	Cancel { who: u8, claim_id: u8 },
+	Vote { who: u8, validator: u8, amount: u16 },
+	ApplyVotes,
+	ReferendumVote { who: u8 },
+	AdvanceEra,
+	Reward { amount: u8 },
+	Slash { validator: u8, percent: u8 },
+}
+
+fn staker(who: u8) -> AccountId {
+	1 + who as AccountId % 5
+}
+
+fn validator(index: u8) -> AccountId {
+	[11, 21, 31][index as usize % 3]
+}
+
+/// (staking currency backing the pool, liquid currency in circulation)
+///
+/// Both leave out pending unbonding requests, whose liquid is no longer backed by the pool.
+fn rate() -> (u128, u128) {
+	let pending_liquid = LiquidStaking::pending_unbonding(POOL).1;
+	(
+		LiquidStaking::backing(POOL),
+		LiquidStaking::total_liquid_issuance(POOL).saturating_sub(pending_liquid),
+	)
+}
+
+fn run(actions: Vec<Action>) {
+	ExtBuilder::default().without_genesis_liquid().build().execute_with(|| {
+		let referendum = begin_referendum();
+		start_active_era(1);
+
+		for action in actions.into_iter().take(MAX_STEPS) {
+			let (backing_before, liquid_before) = rate();
+			let mut slashed = false;
+
+			// calls are expected to fail often, only the invariants matter
+			let _ = match action {
+				Action::Bond { who, amount } => LiquidStaking::bond_and_mint(
+					Origin::signed(staker(who)),
+					POOL,
+					amount.into(),
+					None,
+					None,
+				),
+				Action::Unbond { who, amount } => LiquidStaking::request_unbond(
+					Origin::signed(staker(who)),
+					POOL,
+					amount.into(),
+					None,
+					None,
+				),
+				Action::Withdraw { claim_id } =>
+					LiquidStaking::withdraw_unbonded(Origin::signed(1), POOL, claim_id.into()),
+				Action::Cancel { who, claim_id } => LiquidStaking::cancel_unbond(
+					Origin::signed(staker(who)),
+					POOL,
+					claim_id.into(),
+				),
+				Action::Vote { who, validator: index, amount } => LiquidStaking::vote(
+					Origin::signed(staker(who)),
+					POOL,
+					validator(index),
+					amount.into(),
+				),
+				Action::ApplyVotes => LiquidStaking::apply_votes(Origin::root(), POOL),
+				Action::ReferendumVote { who } => Democracy::vote_v2(
+					Origin::signed(staker(who)),
+					referendum,
+					aye(staker(who), LIQUID_CURRENCY_ID),
+					LIQUID_CURRENCY_ID,
+				),
+				Action::AdvanceEra => {
+					start_active_era(active_era() + 1);
+					Ok(())
+				},
+				Action::Reward { amount } => {
+					reward_pot(POOL, 1 + amount as u128);
+					Ok(())
+				},
+				Action::Slash { validator: index, percent } => {
+					slash_validator(validator(index), Perbill::from_percent(percent as u32 % 101));
+					slashed = true;
+					Ok(())
+				},
+			};
+			// completed requests are paid out with the spare weight of the block
+			<LiquidStaking as Hooks<u64>>::on_idle(System::block_number(), u64::MAX);
+
+			LiquidStaking::sanity_check().unwrap();
+
+			let (backing_after, liquid_after) = rate();
+			if !slashed && liquid_before > 0 && liquid_after > 0 {
+				assert!(
+					backing_after.saturating_mul(liquid_before) >=
+						backing_before.saturating_mul(liquid_after),
+					"exchange rate went down: {}/{} -> {}/{}",
+					backing_before,
+					liquid_before,
+					backing_after,
+					liquid_after,
+				);
+			}
+		}
+	});
+}
+
+fn main() {
+	loop {
+		fuzz!(|actions: Vec<Action>| {
+			run(actions);
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A request made and paid out keeps the exchange rate of the pool.
+	#[test]
+	fn bond_unbond_withdraw() {
+		run(vec![
+			Action::Bond { who: 0, amount: 100 },
+			Action::Unbond { who: 0, amount: 500 },
+			Action::AdvanceEra,
+			Action::AdvanceEra,
+			Action::AdvanceEra,
+			Action::Withdraw { claim_id: 0 },
+		]);
+	}
+}
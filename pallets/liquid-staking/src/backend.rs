@@ -0,0 +1,433 @@
+//! Where the pots of the pools stake.
+//!
+//! The pallet bonds, unbonds and nominates through `Config::StakingBackend`. `LocalStaking`
+//! stakes with `pallet_staking` on this chain. `RemoteStaking` encodes the same operations as
+//! `StakingMessage`s for the chain the stake is bonded on, so the liquid currency can be issued
+//! on a different chain, and keeps a copy of the pots' ledgers that the remote chain corrects
+//! with `report_remote_staking`.
+//!
+//! Validators are looked up through the backend as well: approvals, scores, commissions and the
+//! slashes that stop nominations follow the chain the stake is bonded on. `RemoteStaking` knows
+//! the validators of the remote chain from `report_remote_validator`.
+
+use crate::{
+	pallet::{
+		Config, Error, RemoteEra, RemoteLedgers, RemoteMessagesSent, RemoteNominations,
+		RemoteValidators,
+	},
+	BalanceOf, UnbondWait,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+	dispatch::DispatchResult, ensure, sp_runtime::traits::StaticLookup,
+	storage::with_storage_layer, traits::Get, BoundedVec, RuntimeDebug,
+};
+use pallet_staking::{MaxUnlockingChunks, RewardDestination};
+use scale_info::TypeInfo;
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	Perbill,
+};
+use sp_staking::EraIndex;
+use sp_std::{marker::PhantomData, prelude::*};
+
+/// Stake of a pot.
+#[derive(Clone, Copy, Default, PartialEq, Eq, RuntimeDebug)]
+pub struct PotLedger<Balance> {
+	/// Everything bonded, including what is unbonding.
+	pub total: Balance,
+	/// What is at stake in the coming eras.
+	pub active: Balance,
+}
+
+/// Validators nominated by a pot.
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct PotNominations<AccountId> {
+	pub targets: Vec<AccountId>,
+	/// The era the validators were nominated in.
+	pub submitted_in: EraIndex,
+}
+
+/// A validator of the chain the stake is bonded on.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ValidatorStatus<Balance> {
+	/// Share of the rewards it keeps before paying its nominators.
+	pub commission: Perbill,
+	/// Its own stake, slashed along with its nominators.
+	pub self_stake: Balance,
+	/// Reward points it earned in the era before the active one.
+	pub reward_points: u32,
+	/// The latest era it was slashed for, if it ever was.
+	pub last_slash_era: Option<EraIndex>,
+}
+
+/// Staking operations of the pots.
+pub trait StakingBackend<AccountId, Balance> {
+	/// Bond `value` of `stash`, restaking its rewards.
+	fn bond(stash: &AccountId, value: Balance) -> DispatchResult;
+
+	/// Bond `value` more of the bonded `stash`.
+	fn bond_extra(stash: &AccountId, value: Balance) -> DispatchResult;
+
+	/// Start unbonding `value` of the active stake of `stash`.
+	fn unbond(stash: &AccountId, value: Balance) -> DispatchResult;
+
+	/// Bond again up to `value` of the stake `stash` is unbonding, latest chunks first.
+	fn rebond(stash: &AccountId, value: Balance) -> DispatchResult;
+
+	/// Return the stake of `stash` that finished unbonding to `stash`.
+	fn withdraw_unbonded(stash: &AccountId) -> DispatchResult;
+
+	/// Nominate `targets` with the stake of `stash`.
+	fn nominate(stash: &AccountId, targets: Vec<AccountId>) -> DispatchResult;
+
+	/// Stop nominating with the stake of `stash`.
+	fn chill(stash: &AccountId) -> DispatchResult;
+
+	/// Stake of `stash`, if it is bonded.
+	fn ledger(stash: &AccountId) -> Option<PotLedger<Balance>>;
+
+	/// Staking currency of `stash` held on this chain for its stake.
+	fn locked(stash: &AccountId) -> Balance;
+
+	/// Validators `stash` nominates, if any.
+	fn nominations(stash: &AccountId) -> Option<PotNominations<AccountId>>;
+
+	/// Era stake is bonded and unbonded in.
+	fn current_era() -> Option<EraIndex>;
+
+	/// Era validators are rewarded and slashed in.
+	fn active_era() -> Option<EraIndex>;
+
+	/// `validator`, if it is a validator.
+	fn validator(validator: &AccountId) -> Option<ValidatorStatus<Balance>>;
+
+	/// Commission and latest slash era of `validator`, if it is a validator. Reads at most
+	/// `VALIDATOR_CHECK_READS` items, for the nominations checked every block.
+	fn validator_check(validator: &AccountId) -> Option<(Perbill, Option<EraIndex>)>;
+}
+
+/// Storage items `StakingBackend::validator_check` reads at most.
+pub const VALIDATOR_CHECK_READS: u64 = 2;
+
+/// Stakes with `pallet_staking` on this chain. Each pot is its own controller.
+pub struct LocalStaking<T>(PhantomData<T>);
+
+impl<T: pallet_staking::Config> LocalStaking<T> {
+	fn origin(stash: &T::AccountId) -> T::Origin {
+		frame_system::RawOrigin::Signed(stash.clone()).into()
+	}
+
+	/// Era of the last nonzero slash of `validator`. Spans are kept for stashes that were never
+	/// slashed as well, with era 0 standing for no slash.
+	fn last_slash_era(validator: &T::AccountId) -> Option<EraIndex> {
+		pallet_staking::Pallet::<T>::slashing_spans(validator)
+			.map(|spans| spans.last_nonzero_slash())
+			.filter(|era| *era > 0)
+	}
+}
+
+impl<T: pallet_staking::Config> StakingBackend<T::AccountId, BalanceOf<T>> for LocalStaking<T> {
+	fn bond(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		pallet_staking::Pallet::<T>::bond(
+			Self::origin(stash),
+			T::Lookup::unlookup(stash.clone()),
+			value,
+			RewardDestination::Staked,
+		)
+	}
+
+	fn bond_extra(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		pallet_staking::Pallet::<T>::bond_extra(Self::origin(stash), value)
+	}
+
+	fn unbond(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		pallet_staking::Pallet::<T>::unbond(Self::origin(stash), value)
+	}
+
+	fn rebond(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		pallet_staking::Pallet::<T>::rebond(Self::origin(stash), value)
+			.map(|_| ())
+			.map_err(|e| e.error)
+	}
+
+	fn withdraw_unbonded(stash: &T::AccountId) -> DispatchResult {
+		pallet_staking::Pallet::<T>::withdraw_unbonded(Self::origin(stash), 0)
+			.map(|_| ())
+			.map_err(|e| e.error)
+	}
+
+	fn nominate(stash: &T::AccountId, targets: Vec<T::AccountId>) -> DispatchResult {
+		pallet_staking::Pallet::<T>::nominate(
+			Self::origin(stash),
+			targets.into_iter().map(T::Lookup::unlookup).collect(),
+		)
+	}
+
+	fn chill(stash: &T::AccountId) -> DispatchResult {
+		pallet_staking::Pallet::<T>::chill(Self::origin(stash))
+	}
+
+	fn ledger(stash: &T::AccountId) -> Option<PotLedger<BalanceOf<T>>> {
+		pallet_staking::Pallet::<T>::ledger(stash)
+			.map(|ledger| PotLedger { total: ledger.total, active: ledger.active })
+	}
+
+	fn locked(stash: &T::AccountId) -> BalanceOf<T> {
+		Self::ledger(stash).map_or_else(Zero::zero, |ledger| ledger.total)
+	}
+
+	fn nominations(stash: &T::AccountId) -> Option<PotNominations<T::AccountId>> {
+		pallet_staking::Pallet::<T>::nominators(stash).map(|nominations| PotNominations {
+			targets: nominations.targets.into_inner(),
+			submitted_in: nominations.submitted_in,
+		})
+	}
+
+	fn current_era() -> Option<EraIndex> {
+		pallet_staking::Pallet::<T>::current_era()
+	}
+
+	fn active_era() -> Option<EraIndex> {
+		pallet_staking::Pallet::<T>::active_era().map(|era| era.index)
+	}
+
+	fn validator(validator: &T::AccountId) -> Option<ValidatorStatus<BalanceOf<T>>> {
+		if !pallet_staking::Validators::<T>::contains_key(validator) {
+			return None
+		}
+		let reward_points = Self::active_era()
+			.and_then(|era| era.checked_sub(1))
+			.and_then(|era| {
+				pallet_staking::Pallet::<T>::eras_reward_points(era)
+					.individual
+					.get(validator)
+					.copied()
+			})
+			.unwrap_or_default();
+		Some(ValidatorStatus {
+			commission: pallet_staking::Validators::<T>::get(validator).commission,
+			self_stake: pallet_staking::Pallet::<T>::slashable_balance_of(validator),
+			reward_points,
+			last_slash_era: Self::last_slash_era(validator),
+		})
+	}
+
+	fn validator_check(validator: &T::AccountId) -> Option<(Perbill, Option<EraIndex>)> {
+		// `Validators` has a default, so its value tells nothing about the key
+		pallet_staking::Validators::<T>::try_get(validator)
+			.ok()
+			.map(|prefs| (prefs.commission, Self::last_slash_era(validator)))
+	}
+}
+
+
+/// Staking operation of a pot, for the chain its stake is bonded on.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
+pub enum StakingMessage<AccountId, Balance> {
+	/// Bond `value` of `stash`, sent along with the message, restaking its rewards.
+	Bond { stash: AccountId, value: Balance },
+	/// Bond `value` more of `stash`, sent along with the message.
+	BondExtra { stash: AccountId, value: Balance },
+	Unbond { stash: AccountId, value: Balance },
+	Rebond { stash: AccountId, value: Balance },
+	/// Send the stake of `stash` that finished unbonding back to `stash`.
+	WithdrawUnbonded { stash: AccountId },
+	Nominate { stash: AccountId, targets: Vec<AccountId> },
+	Chill { stash: AccountId },
+}
+
+/// Delivers staking messages to the chain the stake is bonded on, e.g. as XCM `Transact`s
+/// along with the transfers of the staking currency they carry.
+pub trait SendStakingMessage<AccountId, Balance> {
+	fn send(message: StakingMessage<AccountId, Balance>) -> DispatchResult;
+}
+
+/// Ledger of a pot on the remote chain.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct RemoteLedger<Balance> {
+	/// Everything bonded, including what is unbonding.
+	pub total: Balance,
+	/// What is at stake in the coming eras.
+	pub active: Balance,
+	/// Stake being unbonded as (era it unlocks in, amount), oldest first.
+	pub unlocking: BoundedVec<(EraIndex, Balance), MaxUnlockingChunks>,
+}
+
+/// Stakes on a remote chain through the messages `Sender` delivers.
+///
+/// The ledgers are updated as the remote chain is expected to update its own, so the exchange
+/// rate follows without waiting for the remote chain. Its reports replace them, bringing in
+/// rewards and slashes, once the remote chain processed every message sent. The stake withdrawn
+/// with `withdraw_unbonded` must be back in the pot before it is paid out.
+pub struct RemoteStaking<T, Sender>(PhantomData<(T, Sender)>);
+
+impl<T: Config, Sender> RemoteStaking<T, Sender> {
+	fn mutate_ledger(
+		stash: &T::AccountId,
+		f: impl FnOnce(&mut RemoteLedger<BalanceOf<T>>) -> DispatchResult,
+	) -> DispatchResult {
+		RemoteLedgers::<T>::try_mutate(stash, |ledger| {
+			f(ledger.as_mut().ok_or(Error::<T>::PotNotBonded)?)
+		})
+	}
+
+	/// Send `message` for `stash`, counting it for the reports of the remote chain.
+	fn send(
+		stash: &T::AccountId,
+		message: StakingMessage<T::AccountId, BalanceOf<T>>,
+	) -> DispatchResult
+	where
+		Sender: SendStakingMessage<T::AccountId, BalanceOf<T>>,
+	{
+		RemoteMessagesSent::<T>::mutate(stash, |sent| *sent = sent.saturating_add(1));
+		Sender::send(message)
+	}
+}
+
+impl<T: Config, Sender> StakingBackend<T::AccountId, BalanceOf<T>> for RemoteStaking<T, Sender>
+where
+	Sender: SendStakingMessage<T::AccountId, BalanceOf<T>>,
+{
+	fn bond(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		with_storage_layer(|| {
+			ensure!(!RemoteLedgers::<T>::contains_key(stash), Error::<T>::PotAlreadyBonded);
+			RemoteLedgers::<T>::insert(
+				stash,
+				RemoteLedger { total: value, active: value, unlocking: Default::default() },
+			);
+			Self::send(stash, StakingMessage::Bond { stash: stash.clone(), value })
+		})
+	}
+
+	fn bond_extra(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		with_storage_layer(|| {
+			Self::mutate_ledger(stash, |ledger| {
+				ledger.total = ledger.total.saturating_add(value);
+				ledger.active = ledger.active.saturating_add(value);
+				Ok(())
+			})?;
+			Self::send(stash, StakingMessage::BondExtra { stash: stash.clone(), value })
+		})
+	}
+
+	fn unbond(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		with_storage_layer(|| {
+			let era = RemoteEra::<T>::get()
+				.ok_or(Error::<T>::CurrentEraNotSet)?
+				.saturating_add(UnbondWait::<T>::get());
+			Self::mutate_ledger(stash, |ledger| {
+				ensure!(value <= ledger.active, Error::<T>::InsufficientPotStake);
+				ledger.active = ledger.active.saturating_sub(value);
+				let mut unlocking = ledger.unlocking.clone().into_inner();
+				match unlocking.last_mut() {
+					Some(chunk) if chunk.0 == era => chunk.1 = chunk.1.saturating_add(value),
+					_ => unlocking.push((era, value)),
+				}
+				ledger.unlocking =
+					unlocking.try_into().map_err(|_| Error::<T>::TooManyUnlockingChunks)?;
+				Ok(())
+			})?;
+			Self::send(stash, StakingMessage::Unbond { stash: stash.clone(), value })
+		})
+	}
+
+	fn rebond(stash: &T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+		with_storage_layer(|| {
+			Self::mutate_ledger(stash, |ledger| {
+				let mut unlocking = ledger.unlocking.clone().into_inner();
+				let mut remaining = value;
+				while let Some(chunk) = unlocking.last_mut() {
+					let rebonded = chunk.1.min(remaining);
+					chunk.1 = chunk.1.saturating_sub(rebonded);
+					ledger.active = ledger.active.saturating_add(rebonded);
+					remaining = remaining.saturating_sub(rebonded);
+					if chunk.1.is_zero() {
+						unlocking.pop();
+					}
+					if remaining.is_zero() {
+						break
+					}
+				}
+				// no more chunks than before
+				ledger.unlocking = unlocking.try_into().unwrap_or_default();
+				Ok(())
+			})?;
+			Self::send(stash, StakingMessage::Rebond { stash: stash.clone(), value })
+		})
+	}
+
+	fn withdraw_unbonded(stash: &T::AccountId) -> DispatchResult {
+		with_storage_layer(|| {
+			let era = RemoteEra::<T>::get().ok_or(Error::<T>::CurrentEraNotSet)?;
+			let mut ledger = RemoteLedgers::<T>::get(stash).ok_or(Error::<T>::PotNotBonded)?;
+			let mut withdrawn: BalanceOf<T> = Zero::zero();
+			ledger.unlocking.retain(|(unlock_era, value)| {
+				let unlocked = *unlock_era <= era;
+				if unlocked {
+					withdrawn = withdrawn.saturating_add(*value);
+				}
+				!unlocked
+			});
+			ledger.total = ledger.total.saturating_sub(withdrawn);
+			if ledger.total.is_zero() {
+				RemoteLedgers::<T>::remove(stash);
+			} else {
+				RemoteLedgers::<T>::insert(stash, ledger);
+			}
+			Self::send(stash, StakingMessage::WithdrawUnbonded { stash: stash.clone() })
+		})
+	}
+
+	fn nominate(stash: &T::AccountId, targets: Vec<T::AccountId>) -> DispatchResult {
+		with_storage_layer(|| {
+			ensure!(RemoteLedgers::<T>::contains_key(stash), Error::<T>::PotNotBonded);
+			let bounded: BoundedVec<_, <T as pallet_staking::Config>::MaxNominations> =
+				targets.clone().try_into().map_err(|_| Error::<T>::InvalidNominationPolicy)?;
+			let submitted_in = RemoteEra::<T>::get().unwrap_or_default();
+			RemoteNominations::<T>::insert(stash, (bounded, submitted_in));
+			Self::send(stash, StakingMessage::Nominate { stash: stash.clone(), targets })
+		})
+	}
+
+	fn chill(stash: &T::AccountId) -> DispatchResult {
+		with_storage_layer(|| {
+			ensure!(RemoteLedgers::<T>::contains_key(stash), Error::<T>::PotNotBonded);
+			RemoteNominations::<T>::remove(stash);
+			Self::send(stash, StakingMessage::Chill { stash: stash.clone() })
+		})
+	}
+
+	fn ledger(stash: &T::AccountId) -> Option<PotLedger<BalanceOf<T>>> {
+		RemoteLedgers::<T>::get(stash)
+			.map(|ledger| PotLedger { total: ledger.total, active: ledger.active })
+	}
+
+	fn locked(_stash: &T::AccountId) -> BalanceOf<T> {
+		Zero::zero()
+	}
+
+	fn nominations(stash: &T::AccountId) -> Option<PotNominations<T::AccountId>> {
+		RemoteNominations::<T>::get(stash).map(|(targets, submitted_in)| PotNominations {
+			targets: targets.into_inner(),
+			submitted_in,
+		})
+	}
+
+	fn current_era() -> Option<EraIndex> {
+		RemoteEra::<T>::get()
+	}
+
+	fn active_era() -> Option<EraIndex> {
+		RemoteEra::<T>::get()
+	}
+
+	fn validator(validator: &T::AccountId) -> Option<ValidatorStatus<BalanceOf<T>>> {
+		RemoteValidators::<T>::get(validator)
+	}
+
+	fn validator_check(validator: &T::AccountId) -> Option<(Perbill, Option<EraIndex>)> {
+		RemoteValidators::<T>::get(validator)
+			.map(|status| (status.commission, status.last_slash_era))
+	}
+}
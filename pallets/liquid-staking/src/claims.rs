@@ -0,0 +1,61 @@
+//! Unbonding requests as non-fungible claims.
+//!
+//! Each unbonding request is an item of the collection of its pool. The owner of the claim is paid
+//! when the request completes, so claims can be traded to exit a pool before the unbonding period
+//! is over.
+
+use crate::{BalanceOf, ClaimId, ClaimOwner, Config, Error, Event, Pallet, PoolId, Pools};
+use frame_support::{
+	pallet_prelude::*,
+	traits::tokens::nonfungibles::{Inspect, Transfer},
+};
+use sp_runtime::FixedPointOperand;
+
+impl<T: Config> Pallet<T> {
+	/// Make `dest` the owner of the claim `claim_id` of `pool_id`.
+	pub(crate) fn do_transfer_claim(
+		pool_id: PoolId,
+		claim_id: ClaimId,
+		dest: T::AccountId,
+	) -> DispatchResult {
+		let owner =
+			ClaimOwner::<T>::get(pool_id, claim_id).ok_or(Error::<T>::UnbondingRequestNotExist)?;
+		if owner != dest {
+			Self::add_claim(&dest)?;
+			Self::remove_claim(&owner);
+			ClaimOwner::<T>::insert(pool_id, claim_id, &dest);
+		}
+
+		Self::deposit_event(Event::ClaimTransferred { pool_id, claim_id, from: owner, to: dest });
+		Ok(())
+	}
+}
+
+impl<T: Config> Inspect<T::AccountId> for Pallet<T>
+where
+	BalanceOf<T>: FixedPointOperand,
+{
+	type ItemId = ClaimId;
+	type CollectionId = PoolId;
+
+	fn owner(collection: &PoolId, item: &ClaimId) -> Option<T::AccountId> {
+		ClaimOwner::<T>::get(collection, item)
+	}
+
+	fn collection_owner(collection: &PoolId) -> Option<T::AccountId> {
+		Pools::<T>::contains_key(collection).then(|| Self::pot_account(*collection))
+	}
+
+	fn can_transfer(collection: &PoolId, item: &ClaimId) -> bool {
+		ClaimOwner::<T>::contains_key(collection, item)
+	}
+}
+
+impl<T: Config> Transfer<T::AccountId> for Pallet<T>
+where
+	BalanceOf<T>: FixedPointOperand,
+{
+	fn transfer(collection: &PoolId, item: &ClaimId, destination: &T::AccountId) -> DispatchResult {
+		Self::do_transfer_claim(*collection, *item, destination.clone())
+	}
+}
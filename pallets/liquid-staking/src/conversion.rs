@@ -0,0 +1,41 @@
+//! Conversions between staking and liquid currency.
+//!
+//! Amounts are converted with a full width `amount * numerator / denominator`, so no precision is
+//! lost to an intermediate rate, and every conversion states how it rounds. Conversions paying
+//! out to a user round down, conversions charging a user round up, so the pot never loses value
+//! to rounding.
+
+use sp_core::U256;
+use sp_runtime::{traits::AtLeast32BitUnsigned, ArithmeticError};
+
+/// How a conversion rounds when the result is not exact.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+	/// Towards zero. Used for amounts paid out by the pallet.
+	Down,
+	/// Away from zero. Used for amounts charged by the pallet.
+	Up,
+}
+
+/// `amount * numerator / denominator`, rounded as asked.
+pub fn mul_div<Balance: AtLeast32BitUnsigned + Copy>(
+	amount: Balance,
+	numerator: u128,
+	denominator: u128,
+	rounding: Rounding,
+) -> Result<Balance, ArithmeticError> {
+	if denominator == 0 {
+		return Err(ArithmeticError::DivisionByZero)
+	}
+	let amount: u128 = amount.try_into().map_err(|_| ArithmeticError::Overflow)?;
+
+	// the product of two u128 always fits in a U256
+	let product = U256::from(amount) * U256::from(numerator);
+	let (mut result, remainder) = product.div_mod(U256::from(denominator));
+	if rounding == Rounding::Up && !remainder.is_zero() {
+		result += U256::one();
+	}
+
+	let result: u128 = result.try_into().map_err(|_| ArithmeticError::Overflow)?;
+	result.try_into().map_err(|_| ArithmeticError::Overflow)
+}
@@ -2,11 +2,13 @@
 #![allow(clippy::unused_unit)]
 #![allow(clippy::too_many_arguments)]
 
-use frame_support::{sp_runtime::traits::StaticLookup, transactional, BoundedVec, PalletId};
+use frame_support::{
+	sp_runtime::traits::StaticLookup, storage::with_storage_layer, transactional, PalletId,
+};
 pub use pallet::*;
 
-#[cfg(test)]
-mod mock;
+#[cfg(any(test, feature = "fuzz"))]
+pub mod mock;
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
@@ -14,35 +16,250 @@ mod benchmarking;
 #[cfg(test)]
 mod tests;
 
-use frame_support::storage::IterableStorageMap;
+pub mod backend;
+mod claims;
+pub mod conversion;
+pub mod migrations;
+mod rebase;
+pub use rebase::RebasingCurrency;
+
+use backend::{RemoteLedger, StakingBackend, ValidatorStatus};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::RuntimeDebug;
 use orml_traits::{currency::MultiReservableCurrency, MultiCurrency};
 pub use pallet::*;
+use scale_info::TypeInfo;
+use sp_std::{collections::btree_map::BTreeMap, prelude::*};
 
+use conversion::Rounding;
 #[cfg(feature = "std")]
 use sp_runtime::{
 	traits::{AccountIdConversion, Saturating, Zero},
 	ArithmeticError, FixedPointNumber, FixedPointOperand,
 };
+use sp_runtime::{
+	traits::{AtLeast32BitUnsigned, SaturatedConversion},
+	PerThing, Perbill,
+};
 use sp_staking::EraIndex;
 
 pub use primitives::{CurrencyId, MintRate};
+
+pub(crate) const LOG_TARGET: &str = "runtime::liquid-staking";
+
 pub type BalanceOf<T> = <T as pallet_staking::Config>::CurrencyBalance;
 
 // Waiting period before tokens are unlocked
 pub type UnbondWait<T> = <T as pallet_staking::Config>::BondingDuration;
 
+/// How liquid currency backs a vote for a validator.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum VotingMode {
+	/// Voted liquid currency is reserved until the votes are applied.
+	Reserve,
+	/// Votes only record an intention. The weight is taken from the voter's liquid balance
+	/// when the votes are applied, so it follows the tokens when they are transferred.
+	Snapshot,
+}
+
+/// Identifier of a liquid staking pool.
+pub type PoolId = u32;
+
+/// Identifier of an unbonding request, the item of its claim within the pool.
+pub type ClaimId = u32;
+
+/// Operations paused in a pool, as bit flags.
+#[derive(
+	Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+)]
+pub struct PauseFlags(pub u8);
+
+impl PauseFlags {
+	/// `bond_and_mint`, and `cancel_unbond` which mints liquid currency again.
+	pub const BOND: Self = Self(1 << 0);
+	/// `request_unbond`.
+	pub const UNBOND: Self = Self(1 << 1);
+	/// `withdraw_unbonded`, and paying out in idle blocks.
+	pub const WITHDRAW: Self = Self(1 << 2);
+	/// `vote`.
+	pub const VOTE: Self = Self(1 << 3);
+
+	/// No operation is paused.
+	pub const fn empty() -> Self {
+		Self(0)
+	}
+
+	/// Every operation is paused.
+	pub const fn all() -> Self {
+		Self(Self::BOND.0 | Self::UNBOND.0 | Self::WITHDRAW.0 | Self::VOTE.0)
+	}
+
+	/// Whether all operations of `other` are paused.
+	pub const fn contains(self, other: Self) -> bool {
+		self.0 & other.0 == other.0
+	}
+}
+
+impl sp_std::ops::BitOr for PauseFlags {
+	type Output = Self;
+
+	fn bitor(self, other: Self) -> Self {
+		Self(self.0 | other.0)
+	}
+}
+
+/// How the pot of a pool selects the validators it nominates.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct NominationPolicy {
+	/// Number of validators the pot nominates.
+	pub targets: u32,
+	/// Share of a validator's selection weight that comes from its `ValidatorScore`.
+	/// The rest comes from the votes of liquid holders.
+	pub score_weight: Perbill,
+}
+
+/// How balances of the liquid currency of a pool relate to the staking currency.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum AccountingMode {
+	/// Liquid balances are fixed and gain value as the mint rate changes with rewards and
+	/// slashes.
+	ExchangeRate,
+	/// Liquid balances are shares of the pot. Amounts are expressed, and reported by
+	/// `RebasingCurrency`, as share × index, so a balance follows the staking currency 1:1 and
+	/// grows with rewards.
+	Rebasing,
+}
+
+/// A liquid staking pool, with its own pot and liquid currency.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct PoolInfo<Balance> {
+	/// Liquid currency minted by the pool.
+	pub liquid_currency_id: CurrencyId,
+	/// Mint rate used while the pool is empty, the pallet's default mint rate if not set.
+	pub mint_rate: Option<MintRate>,
+	/// Minimum staking amount to bond, the pallet's bond threshold if not set.
+	pub bond_threshold: Option<Balance>,
+	/// Minimum liquid amount to unbond, the pallet's unbond threshold if not set.
+	pub unbond_threshold: Option<Balance>,
+	/// How the pot selects the validators it nominates.
+	pub nomination_policy: NominationPolicy,
+	/// How liquid balances are accounted.
+	pub accounting_mode: AccountingMode,
+	/// Currency wrapping the liquid currency as a fixed number of shares, if any.
+	pub wrapped_currency_id: Option<CurrencyId>,
+}
+
+/// Parameters governance can change without a runtime upgrade. Those not set fall back to the
+/// pallet constants.
+#[derive(
+	Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+)]
+pub struct Params<Balance> {
+	/// Overrides `Config::BondThreshold`.
+	pub bond_threshold: Option<Balance>,
+	/// Overrides `Config::UnbondThreshold`.
+	pub unbond_threshold: Option<Balance>,
+	/// Overrides `Config::DefaultMintRate`.
+	pub default_mint_rate: Option<MintRate>,
+	/// Overrides `Config::MaxValidatorCount`.
+	pub max_validator_count: Option<u32>,
+}
+
+/// A pending unbonding request.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct UnbondingRequest<Balance> {
+	/// Staking currency paid out when the request completes.
+	pub staking_amount: Balance,
+	/// Liquid currency held in the pot and burnt when the request completes.
+	pub liquid_amount: Balance,
+	/// Era the stake was unbonded in.
+	pub era: EraIndex,
+}
+
+/// Caps on deposits to a pool, to grow it gradually. `None` is no cap.
+#[derive(
+	Encode, Decode, Clone, Copy, Default, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen,
+)]
+pub struct DepositCaps<Balance> {
+	/// Most staking currency the pot can have bonded.
+	pub max_stake: Option<Balance>,
+	/// Most staking currency that can be bonded in the pool within an era.
+	pub max_era_deposits: Option<Balance>,
+	/// Most liquid currency an account can hold after bonding.
+	pub max_account_liquid: Option<Balance>,
+}
+
+/// Performance of a validator, each part scaled so that the best possible value is 100%.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub struct ValidatorScore {
+	/// Era points of the last completed era, relative to the best approved validator.
+	pub era_points: Perbill,
+	/// What the validator leaves to its nominators, i.e. `100% - commission`.
+	pub commission: Perbill,
+	/// 100% if never slashed, 50% if the last slash is older than the bonding duration and
+	/// nothing otherwise.
+	pub slashing: Perbill,
+	/// Own active stake, relative to the approved validator with the largest own stake.
+	pub self_stake: Perbill,
+	/// Average of all the parts above.
+	pub total: Perbill,
+}
+
+impl ValidatorScore {
+	pub fn new(
+		era_points: Perbill,
+		commission: Perbill,
+		slashing: Perbill,
+		self_stake: Perbill,
+	) -> Self {
+		let sum = [era_points, commission, slashing, self_stake]
+			.iter()
+			.map(|part| part.deconstruct() as u64)
+			.sum::<u64>();
+		let total = Perbill::from_parts((sum / 4) as u32);
+		Self { era_points, commission, slashing, self_stake, total }
+	}
+}
+
+/// `part / whole`, or nothing if `whole` is zero.
+fn share_of<N: AtLeast32BitUnsigned + Copy>(part: N, whole: N) -> Perbill {
+	if whole.is_zero() {
+		Perbill::zero()
+	} else {
+		Perbill::from_rational(part, whole)
+	}
+}
+
+/// Why a validator was taken out of the approved set without governance action.
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug, TypeInfo, MaxEncodedLen)]
+pub enum RemovalReason {
+	/// The validator is no longer a validator candidate in `pallet_staking`.
+	Chilled,
+	/// The validator was slashed.
+	Slashed,
+	/// The validator's commission is above `MaxCommission`.
+	CommissionAboveCap,
+}
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
 	use frame_support::pallet_prelude::*;
-	use frame_system::{ensure_root, pallet_prelude::*};
+	use frame_system::{
+		ensure_root,
+		offchain::{SendTransactionTypes, SubmitTransaction},
+		pallet_prelude::*,
+	};
+	use pallet_staking::ConfigOp;
 
 	/// Configure the pallet by specifying the parameters and types on which it depends.
 	#[pallet::config]
-	pub trait Config: frame_system::Config + pallet_staking::Config {
+	pub trait Config:
+		frame_system::Config + pallet_staking::Config + SendTransactionTypes<Call<Self>>
+	{
 		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 
-		/// This is used to get the account ID for the liquid staking pot
+		/// This is used to get the account IDs of the liquid staking pots
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
 
@@ -57,70 +274,403 @@ pub mod pallet {
 		#[pallet::constant]
 		type StakingCurrencyId: Get<CurrencyId>;
 
-		/// Liquid Currency ID
+		/// Default Mint rate = liquid currency / staking currency, unless set in `Params`.
 		#[pallet::constant]
-		type LiquidCurrencyId: Get<CurrencyId>;
+		type DefaultMintRate: Get<MintRate>;
 
-		/// Default Mint rate = liquid currency / staking currency.
+		/// Staking currency the pot of a new pool must hold. It is bonded at creation and the
+		/// liquid currency minted for it is locked in the pot forever, so the pool is never
+		/// empty.
 		#[pallet::constant]
-		type DefaultMintRate: Get<MintRate>;
+		type MinPoolSeed: Get<BalanceOf<Self>>;
 
-		/// Minimum bond amount of stake currency to mint liquidCurrency.
+		/// Minimum bond amount of stake currency to mint liquidCurrency, unless set in `Params`.
 		#[pallet::constant]
 		type BondThreshold: Get<BalanceOf<Self>>;
 
-		/// Minimum liquid amount for unstaking staked currency.
+		/// Minimum liquid amount for unstaking staked currency, unless set in `Params`.
 		#[pallet::constant]
 		type UnbondThreshold: Get<BalanceOf<Self>>;
 
-		/// Max validator count
+		/// Max validator count, unless set in `Params`.
 		#[pallet::constant]
 		type MaxValidatorCount: Get<u32>;
+
+		/// Maximum number of pending unbonding requests an account can own the claims of, across
+		/// all pools.
+		#[pallet::constant]
+		type MaxUnbondingRequests: Get<u32>;
+
+		/// Maximum number of unbonding requests waiting to be paid out in idle blocks. Requests
+		/// made while the queue is full are left to `withdraw_unbonded`.
+		#[pallet::constant]
+		type MaxQueuedUnbondings: Get<u32>;
+
+		/// Whether completed unbonding requests are paid out in idle blocks.
+		#[pallet::constant]
+		type AutoWithdraw: Get<bool>;
+
+		/// Whether votes reserve liquid currency or are weighted by a balance snapshot.
+		#[pallet::constant]
+		type VotingMode: Get<VotingMode>;
+
+		/// How far the staking currency backing a unit of liquid currency may fall within an era
+		/// before the offchain worker reports it and bonding is paused. This fall is a rise of
+		/// the mint rate above the rate recorded at the start of the era.
+		#[pallet::constant]
+		type RateDropThreshold: Get<Perbill>;
+
+		/// Priority of the unsigned transactions reporting a rate drop.
+		#[pallet::constant]
+		type UnsignedPriority: Get<TransactionPriority>;
+
+		/// Origin that manages the governance settings of the pallet, such as the pools and the
+		/// validators liquid holders are allowed to vote for.
+		type AdminOrigin: EnsureOrigin<Self::Origin>;
+
+		/// Where the pots stake: `backend::LocalStaking` on this chain, or
+		/// `backend::RemoteStaking` on the chain its messages are sent to.
+		type StakingBackend: StakingBackend<Self::AccountId, BalanceOf<Self>>;
+
+		/// Origin of the reports of the chain `backend::RemoteStaking` stakes on.
+		type RemoteOrigin: EnsureOrigin<Self::Origin>;
 	}
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
-	/// The total amount of issued liquid currency.
+	/// Parameters set by governance, overriding the pallet constants.
+	#[pallet::storage]
+	#[pallet::getter(fn params)]
+	pub type PalletParams<T: Config> = StorageValue<_, Params<BalanceOf<T>>, ValueQuery>;
+
+	/// The id of the next pool to be created.
+	#[pallet::storage]
+	#[pallet::getter(fn next_pool_id)]
+	pub type NextPoolId<T: Config> = StorageValue<_, PoolId, ValueQuery>;
+
+	/// Liquid staking pools.
+	#[pallet::storage]
+	#[pallet::getter(fn pools)]
+	pub type Pools<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, PoolInfo<BalanceOf<T>>, OptionQuery>;
+
+	/// The pool minting each liquid or wrapped currency.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_of_currency)]
+	pub type PoolByCurrency<T: Config> =
+		StorageMap<_, Twox64Concat, CurrencyId, PoolId, OptionQuery>;
+
+	/// The total amount of issued liquid currency of each pool.
 	#[pallet::storage]
 	#[pallet::getter(fn total_liquid_issuance)]
-	pub type TotalLiquidIssuance<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
+	pub type TotalLiquidIssuance<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, BalanceOf<T>, ValueQuery>;
+
+	/// The total amount of issued wrapped currency of each pool.
+	#[pallet::storage]
+	#[pallet::getter(fn total_wrapped_issuance)]
+	pub type TotalWrappedIssuance<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, BalanceOf<T>, ValueQuery>;
+
+	/// Staking currency withdrawn from the pot's ledger for unbonding requests that are not paid
+	/// out yet.
+	#[pallet::storage]
+	#[pallet::getter(fn unclaimed)]
+	pub type Unclaimed<T: Config> = StorageMap<_, Twox64Concat, PoolId, BalanceOf<T>, ValueQuery>;
+
+	/// Staking and liquid currency of the pending unbonding requests of each pool. The requests
+	/// are paid a fixed amount of staking currency, so both are left out of the exchange rate.
+	#[pallet::storage]
+	#[pallet::getter(fn pending_unbonding)]
+	pub type PendingUnbonding<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, (BalanceOf<T>, BalanceOf<T>), ValueQuery>;
+
+	/// Operations paused in each pool.
+	#[pallet::storage]
+	#[pallet::getter(fn paused)]
+	pub type Paused<T: Config> = StorageMap<_, Twox64Concat, PoolId, PauseFlags, ValueQuery>;
+
+	/// Pools in emergency exit. Their stake is unbonded and holders redeem their share of it.
+	#[pallet::storage]
+	#[pallet::getter(fn emergency_exit)]
+	pub type EmergencyExit<T: Config> = StorageMap<_, Twox64Concat, PoolId, bool, ValueQuery>;
+
+	/// Deposit caps of each pool.
+	#[pallet::storage]
+	#[pallet::getter(fn deposit_caps)]
+	pub type Caps<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, DepositCaps<BalanceOf<T>>, ValueQuery>;
+
+	/// Staking currency bonded in each pool during the era, reset every era.
+	#[pallet::storage]
+	#[pallet::getter(fn era_deposits)]
+	pub type EraDeposits<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, (EraIndex, BalanceOf<T>), ValueQuery>;
+
+	/// Pending unbonding requests to pay out in idle blocks, in the order they were made.
+	#[pallet::storage]
+	#[pallet::getter(fn unbonding_queue)]
+	pub type UnbondingQueue<T: Config> =
+		StorageValue<_, BoundedVec<(PoolId, ClaimId), T::MaxQueuedUnbondings>, ValueQuery>;
+
+	/// Number of pending unbonding requests each account owns the claim of.
+	#[pallet::storage]
+	#[pallet::getter(fn unbonding_request_count)]
+	pub type UnbondingRequestCount<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, u32, ValueQuery>;
+
+	/// The id of the next unbonding request.
+	#[pallet::storage]
+	#[pallet::getter(fn next_claim_id)]
+	pub type NextClaimId<T: Config> = StorageValue<_, ClaimId, ValueQuery>;
 
-	/// Unbonding requests: AccountId => (Staking amount, Liquid amount, Era Index)
+	/// Unbonding requests of each pool.
 	#[pallet::storage]
 	#[pallet::getter(fn unbonding_requests)]
-	pub type UnbondingRequests<T: Config> = StorageMap<
+	pub type UnbondingRequests<T: Config> = StorageDoubleMap<
 		_,
 		Twox64Concat,
-		T::AccountId,
-		(BalanceOf<T>, BalanceOf<T>, EraIndex),
+		PoolId,
+		Twox64Concat,
+		ClaimId,
+		UnbondingRequest<BalanceOf<T>>,
 		OptionQuery,
 	>;
 
-	/// Validator simple vote count in liquid currency amount
+	/// Owner of the claim on each unbonding request, paid when the request completes.
+	#[pallet::storage]
+	#[pallet::getter(fn claim_owner)]
+	pub type ClaimOwner<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, PoolId, Twox64Concat, ClaimId, T::AccountId, OptionQuery>;
+
+	/// Validator simple vote count in liquid currency amount, per pool
 	/// k-plurality to select winner
 	#[pallet::storage]
 	#[pallet::getter(fn liquid_vote_count)]
-	pub type LiquidVoteCount<T: Config> =
-		StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+	pub type LiquidVoteCount<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		PoolId,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
 
-	/// Voter list by their votes
+	/// Voter list by their votes, per pool
 	#[pallet::storage]
 	#[pallet::getter(fn voters)]
-	pub type Voters<T: Config> =
-		StorageMap<_, Twox64Concat, T::AccountId, BalanceOf<T>, ValueQuery>;
+	pub type Voters<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		PoolId,
+		Twox64Concat,
+		T::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Vote intentions in `VotingMode::Snapshot`: (PoolId, Voter, Validator) => requested weight.
+	/// The weight counted is capped by the voter's liquid balance when votes are applied.
+	#[pallet::storage]
+	#[pallet::getter(fn vote_intents)]
+	pub type VoteIntents<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, PoolId>,
+			NMapKey<Twox64Concat, T::AccountId>,
+			NMapKey<Twox64Concat, T::AccountId>,
+		),
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Validators approved by governance that liquid holders can vote for.
+	#[pallet::storage]
+	#[pallet::getter(fn approved_validators)]
+	pub type ApprovedValidators<T: Config> =
+		CountedStorageMap<_, Twox64Concat, T::AccountId, (), OptionQuery>;
+
+	/// Validators blocked by governance. They can not be approved or voted for.
+	#[pallet::storage]
+	#[pallet::getter(fn blocked_validators)]
+	pub type BlockedValidators<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, (), OptionQuery>;
+
+	/// Scores of the approved validators, refreshed whenever votes are applied.
+	#[pallet::storage]
+	#[pallet::getter(fn validator_score)]
+	pub type ValidatorScores<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ValidatorScore, OptionQuery>;
+
+	/// Highest commission a validator may charge to be voted for and nominated by the pots.
+	#[pallet::storage]
+	#[pallet::getter(fn max_commission)]
+	pub type MaxCommission<T: Config> = StorageValue<_, Perbill, OptionQuery>;
+
+	/// Mint rate of each pool at the start of the era it was recorded in.
+	#[pallet::storage]
+	#[pallet::getter(fn era_mint_rate)]
+	pub type EraMintRate<T: Config> =
+		StorageMap<_, Twox64Concat, PoolId, (EraIndex, MintRate), OptionQuery>;
+
+	/// Era the mint rates of the pools were last recorded in.
+	#[pallet::storage]
+	pub type MintRateEra<T: Config> = StorageValue<_, EraIndex, OptionQuery>;
+
+	/// Ledgers of the pots on the chain `backend::RemoteStaking` stakes on.
+	#[pallet::storage]
+	#[pallet::getter(fn remote_ledger)]
+	pub type RemoteLedgers<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, RemoteLedger<BalanceOf<T>>, OptionQuery>;
+
+	/// Number of messages `backend::RemoteStaking` sent for each pot.
+	#[pallet::storage]
+	#[pallet::getter(fn remote_messages_sent)]
+	pub type RemoteMessagesSent<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, u64, ValueQuery>;
+
+	/// Validators the pots nominate on the remote chain and the era they were nominated in.
+	#[pallet::storage]
+	pub type RemoteNominations<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::AccountId,
+		(BoundedVec<T::AccountId, <T as pallet_staking::Config>::MaxNominations>, EraIndex),
+		OptionQuery,
+	>;
+
+	/// Validators of the remote chain, as last reported.
+	#[pallet::storage]
+	#[pallet::getter(fn remote_validator)]
+	pub type RemoteValidators<T: Config> =
+		StorageMap<_, Twox64Concat, T::AccountId, ValidatorStatus<BalanceOf<T>>, OptionQuery>;
+
+	/// Current era of the remote chain, as last reported.
+	#[pallet::storage]
+	#[pallet::getter(fn remote_era)]
+	pub type RemoteEra<T: Config> = StorageValue<_, EraIndex, OptionQuery>;
 
 	// Pallets use events to inform users when important changes are made.
 	// https://docs.substrate.io/v3/runtime/events-and-errors
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		BondAndMint(BalanceOf<T>, T::AccountId),
-		RequestUnbond(BalanceOf<T>, T::AccountId),
-		Withdraw(T::AccountId),
-		Voted(T::AccountId, T::AccountId, BalanceOf<T>),
-		NominationsApplied(T::AccountId, T::AccountId),
+		/// Staking currency was bonded in a pool and liquid currency minted for it at `rate`.
+		BondAndMint {
+			pool_id: PoolId,
+			who: T::AccountId,
+			staking_amount: BalanceOf<T>,
+			liquid_amount: BalanceOf<T>,
+			rate: MintRate,
+			era: EraIndex,
+		},
+		/// Liquid currency was handed to the pot to unbond its stake at `rate`.
+		RequestUnbond {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			who: T::AccountId,
+			liquid_amount: BalanceOf<T>,
+			staking_amount: BalanceOf<T>,
+			rate: MintRate,
+			era: EraIndex,
+		},
+		/// A completed unbonding request was paid out to the owner of its claim and its liquid
+		/// currency burnt.
+		Withdraw {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			who: T::AccountId,
+			staking_amount: BalanceOf<T>,
+			liquid_amount: BalanceOf<T>,
+			era: EraIndex,
+		},
+		/// An unbonding request was cancelled, its stake rebonded and liquid currency returned at
+		/// `rate`.
+		UnbondCancelled {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			who: T::AccountId,
+			staking_amount: BalanceOf<T>,
+			liquid_amount: BalanceOf<T>,
+			rate: MintRate,
+		},
+		/// The claim of an unbonding request changed owner.
+		ClaimTransferred {
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			from: T::AccountId,
+			to: T::AccountId,
+		},
+		/// Liquid currency was voted for a validator.
+		Voted {
+			pool_id: PoolId,
+			voter: T::AccountId,
+			validator: T::AccountId,
+			liquid_amount: BalanceOf<T>,
+		},
+		/// The pot of a pool nominated `validators`.
+		NominationsApplied { pool_id: PoolId, validators: Vec<T::AccountId> },
+		ValidatorAdded { validator: T::AccountId },
+		ValidatorRemoved { validator: T::AccountId },
+		ValidatorBlocked { validator: T::AccountId },
+		ValidatorUnblocked { validator: T::AccountId },
+		/// An approved validator was removed without governance.
+		ValidatorAutoRemoved { validator: T::AccountId, reason: RemovalReason },
+		/// A validator was dropped from the nominations of a pot.
+		NominationRemoved { pool_id: PoolId, validator: T::AccountId, reason: RemovalReason },
+		MaxCommissionSet { max_commission: Option<Perbill> },
+		PoolCreated { pool_id: PoolId, liquid_currency_id: CurrencyId },
+		WrappedCurrencySet { pool_id: PoolId, wrapped_currency_id: CurrencyId },
+		/// `shares` of liquid currency were locked in the pot for as much wrapped currency.
+		Wrapped { pool_id: PoolId, who: T::AccountId, shares: BalanceOf<T> },
+		/// `shares` of wrapped currency were burnt for as much liquid currency.
+		Unwrapped { pool_id: PoolId, who: T::AccountId, shares: BalanceOf<T> },
+		NominationPolicySet { pool_id: PoolId, policy: NominationPolicy },
+		PauseSet { pool_id: PoolId, flags: PauseFlags },
+		EmergencyExitEnabled { pool_id: PoolId },
+		/// Liquid currency was burnt for its share of the stake of a pool in emergency exit.
+		EmergencyRedeemed {
+			pool_id: PoolId,
+			who: T::AccountId,
+			liquid_amount: BalanceOf<T>,
+			staking_amount: BalanceOf<T>,
+		},
+		DepositCapsSet { pool_id: PoolId, caps: DepositCaps<BalanceOf<T>> },
+		ParamsSet { params: Params<BalanceOf<T>> },
+		/// The mint rate of a pool at the start of `era` differs from the previous era.
+		MintRateChanged {
+			pool_id: PoolId,
+			era: EraIndex,
+			previous: Option<MintRate>,
+			rate: MintRate,
+		},
+		/// The pallet changed the ledger of the pot of a pool. `bonded` and `unclaimed` back the
+		/// liquid currency.
+		PotLedgerUpdated {
+			pool_id: PoolId,
+			bonded: BalanceOf<T>,
+			active: BalanceOf<T>,
+			unclaimed: BalanceOf<T>,
+		},
+		/// The mint rate of a pool rose from `snapshot`, recorded at the start of `era`, to `rate`
+		/// beyond `Config::RateDropThreshold`. Bonding in the pool is paused.
+		RateDropDetected { pool_id: PoolId, era: EraIndex, snapshot: MintRate, rate: MintRate },
+		/// The mint rate of a pool at the start of `era`, recorded once every era.
+		MintRateRecorded { pool_id: PoolId, era: EraIndex, rate: MintRate },
+		/// A slash of the pot's unlocking stake left `remaining` of the `pending` staking
+		/// currency owed to the unbonding requests of a pool, which were cut pro rata.
+		UnbondingRequestsSlashed {
+			pool_id: PoolId,
+			pending: BalanceOf<T>,
+			remaining: BalanceOf<T>,
+		},
 	}
 
 	// Errors inform users that something went wrong.
@@ -130,14 +680,127 @@ pub mod pallet {
 		BelowBondThreshold,
 		/// The unbond amount in Liquid currency is below threshold
 		BelowUnbondThreshold,
-		/// User already has a redeem request that has not been claimed yet
-		UnclaimedRedeemRequestAlreadyExist,
 		/// Era not set by the session
 		CurrentEraNotSet,
 		/// Unbonding request not found for the claim
 		UnbondingRequestNotExist,
+		/// The caller does not own the claim
+		NotClaimOwner,
 		/// Unbonding period has not elapsed
 		UnbondingWaitNotComplete,
+		/// Unbonding period has elapsed, the request can only be withdrawn
+		UnbondingAlreadyComplete,
+		/// The account owns the claims of `MaxUnbondingRequests` pending unbonding requests
+		TooManyUnbondingRequests,
+		/// Voter does not hold enough liquid currency for the vote
+		InsufficientLiquidBalance,
+		/// The account is not a validator candidate in staking
+		NotAValidator,
+		/// The validator is not in the approved validator set
+		ValidatorNotApproved,
+		/// The validator is already in the approved validator set
+		ValidatorAlreadyApproved,
+		/// The validator is blocked by governance
+		ValidatorBlocked,
+		/// The validator is not blocked
+		ValidatorNotBlocked,
+		/// The approved validator set is full
+		TooManyValidators,
+		/// None of the approved validators has any votes or score
+		NoValidatorsToNominate,
+		/// The validator charges more than `MaxCommission`
+		CommissionTooHigh,
+		/// The pool does not exist
+		PoolNotFound,
+		/// The currency is the staking currency or already minted by a pool
+		LiquidCurrencyInUse,
+		/// The nomination policy must nominate between one and `MaxNominations` validators
+		InvalidNominationPolicy,
+		/// The pool has no wrapped currency
+		NoWrappedCurrency,
+		/// The pool already has a wrapped currency
+		WrappedCurrencyAlreadySet,
+		/// Less liquid currency would be minted than the minimum asked for
+		InsufficientLiquidOut,
+		/// Less staking currency would be unbonded than the minimum asked for
+		InsufficientStakingOut,
+		/// The deadline of the call has passed
+		DeadlinePassed,
+		/// The pot of the new pool does not hold `MinPoolSeed`
+		InsufficientSeed,
+		/// The operation is paused in the pool
+		Paused,
+		/// The pool is in emergency exit
+		EmergencyExitEnabled,
+		/// The pool is not in emergency exit
+		EmergencyExitNotEnabled,
+		/// Not enough of the pool's stake is unlocked yet to redeem the amount
+		EmergencyFundsLocked,
+		/// The bond would take the pool's stake above its cap
+		StakeCapExceeded,
+		/// The bond would take the pool's deposits this era above their cap
+		EraDepositCapExceeded,
+		/// The bond would take the staker's liquid balance above its cap
+		AccountLiquidCapExceeded,
+		/// The amount is zero, or nothing would be paid for it
+		ZeroAmount,
+		/// An account would be left with less than the existential deposit
+		BelowExistentialDeposit,
+		/// The mint rate of the pool is within `RateDropThreshold` of its era snapshot
+		NoRateDrop,
+		/// The pot is not bonded in the staking backend
+		PotNotBonded,
+		/// The pot is already bonded in the staking backend
+		PotAlreadyBonded,
+		/// The pot has less active stake than the amount to unbond
+		InsufficientPotStake,
+		/// The pot is unbonding in too many eras at once
+		TooManyUnlockingChunks,
+		/// A mint rate of zero would mint nothing
+		ZeroMintRate,
+		/// The currency is only issued and burned by its pool
+		IssuedByPool,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T>
+	where
+		BalanceOf<T>: FixedPointOperand,
+	{
+		fn on_initialize(_n: BlockNumberFor<T>) -> Weight {
+			Self::check_nominations().saturating_add(Self::record_mint_rates())
+		}
+
+		fn offchain_worker(_n: BlockNumberFor<T>) {
+			for pool_id in Pools::<T>::iter_keys() {
+				if Self::rate_drop(pool_id).is_none() {
+					continue
+				}
+				let call = Call::report_rate_drop { pool_id };
+				let submitted =
+					SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call.into());
+				if submitted.is_err() {
+					log::warn!(
+						target: LOG_TARGET,
+						"failed to report the rate drop of pool {}",
+						pool_id,
+					);
+				}
+			}
+		}
+
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			if T::AutoWithdraw::get() {
+				Self::sweep_unbonded(remaining_weight)
+			} else {
+				0
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			Self::sanity_check()
+		}
 	}
 
 	// Dispatchable functions allows users to interact with the pallet and invoke state changes.
@@ -148,19 +811,32 @@ pub mod pallet {
 	where
 		BalanceOf<T>: FixedPointOperand,
 	{
-		/// Amount of staking currency to bond and used
-		/// to mint the liquid currency
+		/// Amount of staking currency to bond in pool `pool_id` and used
+		/// to mint its liquid currency
+		///
+		/// Fails if less than `min_liquid_out` would be minted, or after block `deadline`.
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
 		#[transactional]
 		pub fn bond_and_mint(
 			origin: OriginFor<T>,
+			pool_id: PoolId,
 			#[pallet::compact] staking_amount: BalanceOf<T>,
+			min_liquid_out: Option<BalanceOf<T>>,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let staker = ensure_signed(origin.clone())?;
+			Self::ensure_before(deadline)?;
+			let pool = Self::pool(pool_id)?;
+			Self::ensure_active(pool_id, PauseFlags::BOND)?;
+			Self::ensure_non_zero(staking_amount)?;
 
 			// Ensure the amount is above the Bond Threshold
-			ensure!(staking_amount >= T::BondThreshold::get(), Error::<T>::BelowBondThreshold);
-			let pot_account = &Self::account_id();
+			ensure!(
+				staking_amount >= Self::bond_threshold(&pool),
+				Error::<T>::BelowBondThreshold
+			);
+			Self::ensure_can_send(T::StakingCurrencyId::get(), &staker, staking_amount)?;
+			let pot_account = &Self::pot_account(pool_id);
 
 			// transfer staking currency from staker to the pot
 			<T as pallet::Config>::Currency::transfer(
@@ -170,34 +846,42 @@ pub mod pallet {
 				staking_amount,
 			)?;
 
-			let liquid_amount = Self::staking_to_liquid(staking_amount)?;
+			let rate = Self::current_mint_rate(pool_id);
+			let liquid_amount = Self::staking_to_liquid(pool_id, staking_amount)?;
+			if let Some(min_liquid_out) = min_liquid_out {
+				ensure!(
+					Self::from_shares(pool_id, &pool, liquid_amount)? >= min_liquid_out,
+					Error::<T>::InsufficientLiquidOut
+				);
+			}
+			Self::ensure_non_zero(liquid_amount)?;
+			Self::ensure_can_receive(pool.liquid_currency_id, &staker, liquid_amount)?;
+			Self::ensure_within_caps(pool_id, &pool, &staker, staking_amount, liquid_amount)?;
 
 			<T as pallet::Config>::Currency::deposit(
-				T::LiquidCurrencyId::get(),
+				pool.liquid_currency_id,
 				&staker,
 				liquid_amount,
 			)?;
 
-			TotalLiquidIssuance::<T>::mutate(|total| *total = total.saturating_add(liquid_amount));
+			TotalLiquidIssuance::<T>::mutate(pool_id, |total| {
+				*total = total.saturating_add(liquid_amount)
+			});
+			Self::note_deposit(pool_id, staking_amount);
 
-			let pot_origin = frame_system::RawOrigin::Signed(pot_account.clone()).into();
-
-			// FIXME Bond some in genesis config
-			// so never have to bond again and not check ledger
-			let ledger = pallet_staking::Pallet::<T>::ledger(&pot_account);
-			if ledger.is_some() {
-				pallet_staking::Pallet::<T>::bond_extra(pot_origin, staking_amount)?;
-			} else {
-				pallet_staking::Pallet::<T>::bond(
-					pot_origin,
-					T::Lookup::unlookup(pot_account.clone()),
-					staking_amount,
-					pallet_staking::RewardDestination::Controller,
-				)?;
-			}
+			// the pot is bonded since the pool was created
+			T::StakingBackend::bond_extra(pot_account, staking_amount)?;
 
 			// Emit an event.
-			Self::deposit_event(Event::BondAndMint(staking_amount, staker));
+			Self::deposit_event(Event::BondAndMint {
+				pool_id,
+				who: staker,
+				staking_amount,
+				liquid_amount,
+				rate,
+				era: Self::current_era().unwrap_or_default(),
+			});
+			Self::note_pot_ledger(pool_id);
 			// Return a successful result
 			Ok(())
 		}
@@ -206,32 +890,62 @@ pub mod pallet {
 		#[transactional]
 		pub fn vote(
 			origin: OriginFor<T>,
+			pool_id: PoolId,
 			target: T::AccountId,
 			#[pallet::compact] liquid_amount: BalanceOf<T>,
 		) -> DispatchResult {
 			let voter = ensure_signed(origin.clone())?;
+			let pool = Self::pool(pool_id)?;
+			Self::ensure_active(pool_id, PauseFlags::VOTE)?;
+			Self::ensure_non_zero(liquid_amount)?;
+			Self::ensure_votable(&target)?;
+			let liquid_amount = Self::to_shares(pool_id, &pool, liquid_amount)?;
 
-			<<T as pallet::Config>::Currency as MultiReservableCurrency<_>>::reserve(
-				T::LiquidCurrencyId::get(),
-				&voter,
-				liquid_amount,
-			)?;
+			match T::VotingMode::get() {
+				VotingMode::Reserve => {
+					<<T as pallet::Config>::Currency as MultiReservableCurrency<_>>::reserve(
+						pool.liquid_currency_id,
+						&voter,
+						liquid_amount,
+					)?;
 
-			// probably a create if not exist like api is there?
-			let exists = LiquidVoteCount::<T>::try_get(target.clone()).is_ok();
+					// probably a create if not exist like api is there?
+					let exists = LiquidVoteCount::<T>::try_get(pool_id, target.clone()).is_ok();
 
-			if exists {
-				LiquidVoteCount::<T>::mutate(target.clone(), |votes| {
-					votes.saturating_add(liquid_amount);
-				});
-			} else {
-				LiquidVoteCount::<T>::insert(target.clone(), liquid_amount);
-			}
+					if exists {
+						LiquidVoteCount::<T>::mutate(pool_id, target.clone(), |votes| {
+							*votes = votes.saturating_add(liquid_amount);
+						});
+					} else {
+						LiquidVoteCount::<T>::insert(pool_id, target.clone(), liquid_amount);
+					}
+
+					// all of the voter's reserves are released when the votes are applied
+					Voters::<T>::mutate(pool_id, voter.clone(), |reserved| {
+						*reserved = reserved.saturating_add(liquid_amount)
+					});
+				},
+				VotingMode::Snapshot => {
+					// nothing is locked, the balance is checked again when votes are applied
+					let intended = VoteIntents::<T>::iter_prefix_values((pool_id, &voter))
+						.fold(liquid_amount, |total, amount| total.saturating_add(amount));
+					ensure!(
+						intended <=
+							<T as pallet::Config>::Currency::free_balance(
+								pool.liquid_currency_id,
+								&voter
+							),
+						Error::<T>::InsufficientLiquidBalance
+					);
 
-			Voters::<T>::insert(voter.clone(), liquid_amount);
+					VoteIntents::<T>::mutate((pool_id, &voter, &target), |votes| {
+						*votes = votes.saturating_add(liquid_amount)
+					});
+				},
+			}
 
 			// Emit an event.
-			Self::deposit_event(Event::Voted(voter, target, liquid_amount));
+			Self::deposit_event(Event::Voted { pool_id, voter, validator: target, liquid_amount });
 			// Return a successful result
 			Ok(())
 		}
@@ -239,181 +953,1622 @@ pub mod pallet {
 		/// should be called at end of era
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
 		#[transactional]
-		pub fn apply_votes(origin: OriginFor<T>) -> DispatchResult {
+		pub fn apply_votes(origin: OriginFor<T>, pool_id: PoolId) -> DispatchResult {
 			ensure_root(origin)?;
-			// Probably super bad to sort and do unwraps
-			// fix it before going to production
-			let votes =
-				<LiquidVoteCount<T> as IterableStorageMap<T::AccountId, BalanceOf<T>>>::iter()
-					.map(|(tar, votes)| {
-						// clear votes for the next era
-						LiquidVoteCount::<T>::remove(&tar);
-						(tar, votes)
-					})
-					.collect::<Vec<_>>();
-
-			let mut votes: BoundedVec<_, T::MaxValidatorCount> =
-				BoundedVec::try_from(votes).expect("value not expected to be higher");
-			// sort in descending order of votes
-			votes.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-
-			let pot_account = &Self::account_id();
-			let pot_origin = frame_system::RawOrigin::Signed(pot_account.clone()).into();
-			// super naive selection of only top two validators
-			// extremely unsafe to use indexes like this
-			let val1 = T::Lookup::unlookup(votes[0].0.clone());
-			let val2 = T::Lookup::unlookup(votes[1].0.clone());
-			pallet_staking::Pallet::<T>::nominate(pot_origin, vec![val1.clone(), val2.clone()])?;
-			// unreserve voter's money
-			<Voters<T> as IterableStorageMap<T::AccountId, BalanceOf<T>>>::iter().for_each(
-				|(voter, liquid_amount)| {
-					// Clear votes for the next era
-					Voters::<T>::remove(&voter);
-					<<T as pallet::Config>::Currency as MultiReservableCurrency<_>>::unreserve(
-						T::LiquidCurrencyId::get(),
-						&voter,
-						liquid_amount,
-					);
-				},
-			);
+			let pool = Self::pool(pool_id)?;
+			ensure!(!EmergencyExit::<T>::get(pool_id), Error::<T>::EmergencyExitEnabled);
+			Self::prune_validators();
+
+			let votes = match T::VotingMode::get() {
+				// clear votes for the next era
+				VotingMode::Reserve => LiquidVoteCount::<T>::drain_prefix(pool_id).collect(),
+				VotingMode::Snapshot => Self::tally_vote_intents(pool_id, pool.liquid_currency_id),
+			};
+
+			Self::update_scores();
+			let targets = Self::select_targets(&votes, &pool.nomination_policy);
+			ensure!(!targets.is_empty(), Error::<T>::NoValidatorsToNominate);
+
+			T::StakingBackend::nominate(&Self::pot_account(pool_id), targets.clone())?;
+			// unreserve voter's money and clear votes for the next era
+			Voters::<T>::drain_prefix(pool_id).for_each(|(voter, liquid_amount)| {
+				<<T as pallet::Config>::Currency as MultiReservableCurrency<_>>::unreserve(
+					pool.liquid_currency_id,
+					&voter,
+					liquid_amount,
+				);
+			});
 			// Emit an event.
-			Self::deposit_event(Event::NominationsApplied(votes[0].0.clone(), votes[1].0.clone()));
+			Self::deposit_event(Event::NominationsApplied { pool_id, validators: targets });
 			// Return a successful result
 			Ok(())
 		}
 
+		/// Unbond `liquid_amount` of the liquid currency of `pool_id`.
+		///
+		/// Fails if less than `min_staking_out` would be unbonded, or after block `deadline`.
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
 		#[transactional]
 		pub fn request_unbond(
 			origin: OriginFor<T>,
+			pool_id: PoolId,
 			#[pallet::compact] liquid_amount: BalanceOf<T>,
+			min_staking_out: Option<BalanceOf<T>>,
+			deadline: Option<BlockNumberFor<T>>,
 		) -> DispatchResult {
 			let who = ensure_signed(origin.clone())?;
+			Self::ensure_before(deadline)?;
+			let pool = Self::pool(pool_id)?;
+			Self::ensure_active(pool_id, PauseFlags::UNBOND)?;
+			Self::ensure_non_zero(liquid_amount)?;
+			ensure!(
+				liquid_amount >= Self::unbond_threshold(&pool),
+				Error::<T>::BelowUnbondThreshold
+			);
+			let liquid_amount = Self::to_shares(pool_id, &pool, liquid_amount)?;
 
-			let already_requested = UnbondingRequests::<T>::contains_key(&who);
-			ensure!(!already_requested, Error::<T>::UnclaimedRedeemRequestAlreadyExist);
-			let current_era = pallet_staking::Pallet::<T>::current_era();
+			let current_era = Self::current_era();
 			ensure!(current_era.is_some(), Error::<T>::CurrentEraNotSet);
 
-			let _ = <T as pallet::Config>::Currency::transfer(
-				T::LiquidCurrencyId::get(),
+			Self::ensure_can_send(pool.liquid_currency_id, &who, liquid_amount)?;
+			let pot_account = &Self::pot_account(pool_id);
+			<T as pallet::Config>::Currency::transfer(
+				pool.liquid_currency_id,
 				&who,
-				&Self::account_id(),
+				pot_account,
 				liquid_amount,
-			);
+			)?;
 
 			// no rewards/slash are counted once unbonding is requested
-			let staking_amount = Self::liquid_to_staking(liquid_amount)?;
+			let rate = Self::current_mint_rate(pool_id);
+			let staking_amount = Self::liquid_to_staking(pool_id, liquid_amount)?;
+			Self::ensure_non_zero(staking_amount)?;
+			if let Some(min_staking_out) = min_staking_out {
+				ensure!(staking_amount >= min_staking_out, Error::<T>::InsufficientStakingOut);
+			}
+			let claim_id = NextClaimId::<T>::get();
+			NextClaimId::<T>::put(claim_id.checked_add(1).ok_or(ArithmeticError::Overflow)?);
+			Self::add_claim(&who)?;
+			// requests beyond the queue are left to `withdraw_unbonded`
+			let _ = UnbondingQueue::<T>::try_append((pool_id, claim_id));
 			// can unwrap as we checked previously current era exists
+			let era = current_era.unwrap();
 			UnbondingRequests::<T>::insert(
-				&who,
-				(staking_amount, liquid_amount, current_era.unwrap()),
+				pool_id,
+				claim_id,
+				UnbondingRequest { staking_amount, liquid_amount, era },
 			);
+			PendingUnbonding::<T>::mutate(pool_id, |(staking, liquid)| {
+				*staking = staking.saturating_add(staking_amount);
+				*liquid = liquid.saturating_add(liquid_amount);
+			});
+			ClaimOwner::<T>::insert(pool_id, claim_id, &who);
 			// unbond funds from pot account
-			let pot_account = &Self::account_id();
-			let pot_origin = frame_system::RawOrigin::Signed(pot_account.clone()).into();
-			pallet_staking::Pallet::<T>::unbond(pot_origin, staking_amount)?;
+			T::StakingBackend::unbond(pot_account, staking_amount)?;
 
 			// Emit an event.
-			Self::deposit_event(Event::RequestUnbond(liquid_amount, who));
+			Self::deposit_event(Event::RequestUnbond {
+				pool_id,
+				claim_id,
+				who,
+				liquid_amount,
+				staking_amount,
+				rate,
+				era,
+			});
+			Self::note_pot_ledger(pool_id);
 			// Return a successful result
 			Ok(())
 		}
 
+		/// Pay out the completed unbonding request `claim_id` of `pool_id` to the owner of its
+		/// claim. Anyone can call it.
 		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
 		#[transactional]
-		pub fn withdraw_unbonded(origin: OriginFor<T>) -> DispatchResult {
-			let who = ensure_signed(origin.clone())?;
-			// Get the unbonding request
-			let unbonding_request = UnbondingRequests::<T>::get(&who);
-
-			ensure!(unbonding_request.is_some(), Error::<T>::UnbondingRequestNotExist);
-			let (stake_amount, liquid_amount, old_era) = unbonding_request.unwrap();
-
-			let current_era = Self::current_era();
-			ensure!(current_era.is_some(), Error::<T>::CurrentEraNotSet);
-
-			let unbond_wait = UnbondWait::<T>::get();
+		pub fn withdraw_unbonded(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			claim_id: ClaimId,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(!Paused::<T>::get(pool_id).contains(PauseFlags::WITHDRAW), Error::<T>::Paused);
+			Self::do_withdraw_unbonded(pool_id, claim_id)
+		}
 
+		/// Cancel the unbonding request `claim_id` of `pool_id` before it completes. Only the
+		/// owner of the claim can cancel it.
+		///
+		/// The stake is bonded again and the owner gets liquid currency for it at the current
+		/// exchange rate.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(4))]
+		#[transactional]
+		pub fn cancel_unbond(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			claim_id: ClaimId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool = Self::pool(pool_id)?;
+			Self::ensure_active(pool_id, PauseFlags::BOND)?;
+			let UnbondingRequest { staking_amount, liquid_amount, era } =
+				UnbondingRequests::<T>::get(pool_id, claim_id)
+					.ok_or(Error::<T>::UnbondingRequestNotExist)?;
 			ensure!(
-				old_era + unbond_wait <= current_era.unwrap(),
-				Error::<T>::UnbondingWaitNotComplete
+				ClaimOwner::<T>::get(pool_id, claim_id).as_ref() == Some(&who),
+				Error::<T>::NotClaimOwner
 			);
 
-			let pot_account = Self::account_id();
-			let pot_origin = frame_system::RawOrigin::Signed(pot_account.clone()).into();
-			let _ = pallet_staking::Pallet::<T>::withdraw_unbonded(pot_origin, 0);
-
-			// burn liquid amount
-			<T as pallet::Config>::Currency::withdraw(
-				T::LiquidCurrencyId::get(),
-				&pot_account,
-				liquid_amount,
-			)?;
+			let current_era = Self::current_era().ok_or(Error::<T>::CurrentEraNotSet)?;
+			ensure!(
+				current_era < era.saturating_add(UnbondWait::<T>::get()),
+				Error::<T>::UnbondingAlreadyComplete
+			);
 
-			// transfer redeemed_staking to redeemer.
+			// the stake goes back to the holders at their rate, whatever part of it is rebonded
+			let rate = Self::current_mint_rate(pool_id);
+			let returned = Self::staking_to_liquid(pool_id, staking_amount)?;
+			Self::release_pending(pool_id, staking_amount, liquid_amount);
+			let pot_account = Self::pot_account(pool_id);
+			T::StakingBackend::rebond(&pot_account, staking_amount)?;
+			Self::ensure_can_receive(pool.liquid_currency_id, &who, returned)?;
+			if returned <= liquid_amount {
+				let burnt = liquid_amount.saturating_sub(returned);
+				<T as pallet::Config>::Currency::withdraw(
+					pool.liquid_currency_id,
+					&pot_account,
+					burnt,
+				)?;
+				TotalLiquidIssuance::<T>::mutate(pool_id, |total| {
+					*total = total.saturating_sub(burnt)
+				});
+			} else {
+				let minted = returned.saturating_sub(liquid_amount);
+				<T as pallet::Config>::Currency::deposit(
+					pool.liquid_currency_id,
+					&pot_account,
+					minted,
+				)?;
+				TotalLiquidIssuance::<T>::mutate(pool_id, |total| {
+					*total = total.saturating_add(minted)
+				});
+			}
 			<T as pallet::Config>::Currency::transfer(
-				T::StakingCurrencyId::get(),
+				pool.liquid_currency_id,
 				&pot_account,
 				&who,
-				stake_amount,
+				returned,
 			)?;
+			UnbondingRequests::<T>::remove(pool_id, claim_id);
+			ClaimOwner::<T>::remove(pool_id, claim_id);
+			Self::remove_claim(&who);
+			Self::dequeue_unbonding(pool_id, claim_id);
 
-			TotalLiquidIssuance::<T>::mutate(|total| *total = total.saturating_sub(liquid_amount));
-
-			// Emit an event.
-			Self::deposit_event(Event::Withdraw(who));
-			// Return a successful result
+			Self::deposit_event(Event::UnbondCancelled {
+				pool_id,
+				claim_id,
+				who,
+				staking_amount,
+				liquid_amount: returned,
+				rate,
+			});
+			Self::note_pot_ledger(pool_id);
 			Ok(())
 		}
-	}
 
-	impl<T: Config> Pallet<T>
-	where
-		BalanceOf<T>: FixedPointOperand,
-	{
-		/// Module account id
-		pub fn account_id() -> T::AccountId {
-			T::PalletId::get().into_account_truncating()
+		/// Transfer the claim `claim_id` of `pool_id` to `dest`, who is paid when the unbonding
+		/// completes. The claim counts towards the `MaxUnbondingRequests` of `dest`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 3))]
+		#[transactional]
+		pub fn transfer_claim(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			claim_id: ClaimId,
+			dest: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let dest = T::Lookup::lookup(dest)?;
+			ensure!(
+				ClaimOwner::<T>::get(pool_id, claim_id).as_ref() == Some(&who),
+				Error::<T>::NotClaimOwner
+			);
+			Self::do_transfer_claim(pool_id, claim_id, dest)
 		}
 
-		pub fn staking_to_liquid(
-			staking_amount: BalanceOf<T>,
-		) -> Result<BalanceOf<T>, DispatchError> {
-			Self::current_mint_rate()
-				.checked_mul_int(staking_amount)
-				.ok_or(DispatchError::Arithmetic(ArithmeticError::Overflow))
-		}
+		/// Wrap `liquid_amount` of the liquid currency of `pool_id` into its wrapped currency,
+		/// which is a fixed number of shares of the pot.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3))]
+		#[transactional]
+		pub fn wrap(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			#[pallet::compact] liquid_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool = Self::pool(pool_id)?;
+			let wrapped_currency_id =
+				pool.wrapped_currency_id.ok_or(Error::<T>::NoWrappedCurrency)?;
+			Self::ensure_non_zero(liquid_amount)?;
+			let shares = Self::to_shares(pool_id, &pool, liquid_amount)?;
+			Self::ensure_can_send(pool.liquid_currency_id, &who, shares)?;
+			Self::ensure_can_receive(wrapped_currency_id, &who, shares)?;
+
+			// the pot holds the wrapped shares
+			<T as pallet::Config>::Currency::transfer(
+				pool.liquid_currency_id,
+				&who,
+				&Self::pot_account(pool_id),
+				shares,
+			)?;
+			<T as pallet::Config>::Currency::deposit(wrapped_currency_id, &who, shares)?;
+			TotalWrappedIssuance::<T>::mutate(pool_id, |total| {
+				*total = total.saturating_add(shares)
+			});
+
+			Self::deposit_event(Event::Wrapped { pool_id, who, shares });
+			Ok(())
+		}
+
+		/// Turn `wrapped_amount` of the wrapped currency of `pool_id` back into its liquid
+		/// currency.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(3))]
+		#[transactional]
+		pub fn unwrap(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			#[pallet::compact] wrapped_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool = Self::pool(pool_id)?;
+			let wrapped_currency_id =
+				pool.wrapped_currency_id.ok_or(Error::<T>::NoWrappedCurrency)?;
+			Self::ensure_non_zero(wrapped_amount)?;
+			Self::ensure_can_send(wrapped_currency_id, &who, wrapped_amount)?;
+			Self::ensure_can_receive(pool.liquid_currency_id, &who, wrapped_amount)?;
+
+			<T as pallet::Config>::Currency::withdraw(wrapped_currency_id, &who, wrapped_amount)?;
+			<T as pallet::Config>::Currency::transfer(
+				pool.liquid_currency_id,
+				&Self::pot_account(pool_id),
+				&who,
+				wrapped_amount,
+			)?;
+			TotalWrappedIssuance::<T>::mutate(pool_id, |total| {
+				*total = total.saturating_sub(wrapped_amount)
+			});
+
+			Self::deposit_event(Event::Unwrapped { pool_id, who, shares: wrapped_amount });
+			Ok(())
+		}
+
+		/// Add a validator to the set liquid holders can vote for.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn add_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				!BlockedValidators::<T>::contains_key(&validator),
+				Error::<T>::ValidatorBlocked
+			);
+			ensure!(T::StakingBackend::validator(&validator).is_some(), Error::<T>::NotAValidator);
+			ensure!(
+				!ApprovedValidators::<T>::contains_key(&validator),
+				Error::<T>::ValidatorAlreadyApproved
+			);
+			ensure!(
+				ApprovedValidators::<T>::count() < Self::max_validator_count(),
+				Error::<T>::TooManyValidators
+			);
+
+			ApprovedValidators::<T>::insert(&validator, ());
+
+			Self::deposit_event(Event::ValidatorAdded { validator });
+			Ok(())
+		}
+
+		/// Remove a validator from the set liquid holders can vote for.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn remove_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				ApprovedValidators::<T>::contains_key(&validator),
+				Error::<T>::ValidatorNotApproved
+			);
+			ApprovedValidators::<T>::remove(&validator);
+			ValidatorScores::<T>::remove(&validator);
+
+			Self::deposit_event(Event::ValidatorRemoved { validator });
+			Ok(())
+		}
+
+		/// Block a validator, removing it from the approved set if it is there.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2))]
+		pub fn block_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				!BlockedValidators::<T>::contains_key(&validator),
+				Error::<T>::ValidatorBlocked
+			);
+			ApprovedValidators::<T>::remove(&validator);
+			ValidatorScores::<T>::remove(&validator);
+			BlockedValidators::<T>::insert(&validator, ());
+
+			Self::deposit_event(Event::ValidatorBlocked { validator });
+			Ok(())
+		}
+
+		/// Lift the block of a validator. It has to be added again to receive votes.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn unblock_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				BlockedValidators::<T>::contains_key(&validator),
+				Error::<T>::ValidatorNotBlocked
+			);
+			BlockedValidators::<T>::remove(&validator);
+
+			Self::deposit_event(Event::ValidatorUnblocked { validator });
+			Ok(())
+		}
+
+		/// Set the highest commission of validators the pots nominate, or `None` for no cap.
+		///
+		/// Nominated validators above the new cap are dropped at the next block.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_max_commission(
+			origin: OriginFor<T>,
+			max_commission: Option<Perbill>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			MaxCommission::<T>::set(max_commission);
+
+			Self::deposit_event(Event::MaxCommissionSet { max_commission });
+			Ok(())
+		}
+
+		/// Create a pool minting `liquid_currency_id` against staking currency.
+		///
+		/// The pot of the pool, `pot_account(next_pool_id)`, must hold `MinPoolSeed` beforehand.
+		/// Thresholds and mint rate left as `None` follow the pallet constants.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(6))]
+		#[transactional]
+		pub fn create_pool(
+			origin: OriginFor<T>,
+			liquid_currency_id: CurrencyId,
+			mint_rate: Option<MintRate>,
+			bond_threshold: Option<BalanceOf<T>>,
+			unbond_threshold: Option<BalanceOf<T>>,
+			nomination_policy: NominationPolicy,
+			accounting_mode: AccountingMode,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+
+			ensure!(
+				liquid_currency_id != T::StakingCurrencyId::get() &&
+					!PoolByCurrency::<T>::contains_key(liquid_currency_id),
+				Error::<T>::LiquidCurrencyInUse
+			);
+			Self::ensure_valid_policy(&nomination_policy)?;
+			ensure!(!mint_rate.map_or(false, |rate| rate.is_zero()), Error::<T>::ZeroMintRate);
+
+			let pool_id = NextPoolId::<T>::get();
+			NextPoolId::<T>::put(pool_id.checked_add(1).ok_or(ArithmeticError::Overflow)?);
+			Pools::<T>::insert(
+				pool_id,
+				PoolInfo {
+					liquid_currency_id,
+					mint_rate,
+					bond_threshold,
+					unbond_threshold,
+					nomination_policy,
+					accounting_mode,
+					wrapped_currency_id: None,
+				},
+			);
+			PoolByCurrency::<T>::insert(liquid_currency_id, pool_id);
+
+			// bond the seed and lock the liquid currency minted for it in the pot
+			let seed = T::MinPoolSeed::get();
+			let pot_account = Self::pot_account(pool_id);
+			ensure!(
+				<T as pallet::Config>::Currency::free_balance(
+					T::StakingCurrencyId::get(),
+					&pot_account
+				) >= seed,
+				Error::<T>::InsufficientSeed
+			);
+			let seed_liquid = Self::staking_to_liquid(pool_id, seed)?;
+			T::StakingBackend::bond(&pot_account, seed)?;
+			<T as pallet::Config>::Currency::deposit(
+				liquid_currency_id,
+				&pot_account,
+				seed_liquid,
+			)?;
+			TotalLiquidIssuance::<T>::insert(pool_id, seed_liquid);
+
+			Self::deposit_event(Event::PoolCreated { pool_id, liquid_currency_id });
+			Self::note_pot_ledger(pool_id);
+			Ok(())
+		}
+
+		/// Change how the pot of `pool_id` selects validators. Applies from the next
+		/// `apply_votes`.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_nomination_policy(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			nomination_policy: NominationPolicy,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::ensure_valid_policy(&nomination_policy)?;
+
+			Pools::<T>::try_mutate(pool_id, |pool| -> DispatchResult {
+				let pool = pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+				pool.nomination_policy = nomination_policy;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::NominationPolicySet { pool_id, policy: nomination_policy });
+			Ok(())
+		}
+
+		/// Set the currency that wraps the liquid currency of `pool_id`. It can only be set once.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2))]
+		pub fn set_wrapped_currency(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			wrapped_currency_id: CurrencyId,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				wrapped_currency_id != T::StakingCurrencyId::get() &&
+					!PoolByCurrency::<T>::contains_key(wrapped_currency_id),
+				Error::<T>::LiquidCurrencyInUse
+			);
+
+			Pools::<T>::try_mutate(pool_id, |pool| -> DispatchResult {
+				let pool = pool.as_mut().ok_or(Error::<T>::PoolNotFound)?;
+				ensure!(pool.wrapped_currency_id.is_none(), Error::<T>::WrappedCurrencyAlreadySet);
+				pool.wrapped_currency_id = Some(wrapped_currency_id);
+				Ok(())
+			})?;
+			PoolByCurrency::<T>::insert(wrapped_currency_id, pool_id);
+
+			Self::deposit_event(Event::WrappedCurrencySet { pool_id, wrapped_currency_id });
+			Ok(())
+		}
+
+		/// Change the parameters of the pallet. `Remove` falls back to the pallet constant.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_params(
+			origin: OriginFor<T>,
+			bond_threshold: ConfigOp<BalanceOf<T>>,
+			unbond_threshold: ConfigOp<BalanceOf<T>>,
+			default_mint_rate: ConfigOp<MintRate>,
+			max_validator_count: ConfigOp<u32>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			ensure!(
+				!matches!(default_mint_rate, ConfigOp::Set(rate) if rate.is_zero()),
+				Error::<T>::ZeroMintRate
+			);
+
+			macro_rules! config_op_exp {
+				($field:expr, $op:ident) => {
+					match $op {
+						ConfigOp::Noop => (),
+						ConfigOp::Set(v) => $field = Some(v),
+						ConfigOp::Remove => $field = None,
+					}
+				};
+			}
+
+			let params = PalletParams::<T>::mutate(|params| {
+				config_op_exp!(params.bond_threshold, bond_threshold);
+				config_op_exp!(params.unbond_threshold, unbond_threshold);
+				config_op_exp!(params.default_mint_rate, default_mint_rate);
+				config_op_exp!(params.max_validator_count, max_validator_count);
+				*params
+			});
+
+			Self::deposit_event(Event::ParamsSet { params });
+			Ok(())
+		}
+
+		/// Set the deposit caps of `pool_id`, replacing the previous ones.
+		///
+		/// Caps only limit new bonds, what is above a lowered cap stays.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_deposit_caps(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			caps: DepositCaps<BalanceOf<T>>,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::pool(pool_id)?;
+
+			Caps::<T>::insert(pool_id, caps);
+
+			Self::deposit_event(Event::DepositCapsSet { pool_id, caps });
+			Ok(())
+		}
+
+		/// Set the operations paused in `pool_id`, replacing the ones paused before.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_pause(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			flags: PauseFlags,
+		) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::pool(pool_id)?;
+
+			Paused::<T>::insert(pool_id, flags);
+
+			Self::deposit_event(Event::PauseSet { pool_id, flags });
+			Ok(())
+		}
+
+		/// Pause bonding in `pool_id` as its mint rate rose beyond `Config::RateDropThreshold`
+		/// since the start of the era.
+		///
+		/// Submitted by the offchain worker as an unsigned transaction. The rate is checked again
+		/// on chain.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 1))]
+		pub fn report_rate_drop(origin: OriginFor<T>, pool_id: PoolId) -> DispatchResult {
+			ensure_none(origin)?;
+			let (era, snapshot, rate) = Self::rate_drop(pool_id).ok_or(Error::<T>::NoRateDrop)?;
+
+			let flags = Paused::<T>::get(pool_id) | PauseFlags::BOND;
+			Paused::<T>::insert(pool_id, flags);
+
+			Self::deposit_event(Event::RateDropDetected { pool_id, era, snapshot, rate });
+			Self::deposit_event(Event::PauseSet { pool_id, flags });
+			Ok(())
+		}
+
+		/// Record the ledger of the pot `stash` and the current era reported by the chain
+		/// `backend::RemoteStaking` stakes on.
+		///
+		/// The report replaces the ledger kept from the messages sent, bringing in the rewards
+		/// and slashes of the remote chain. `None` reports the pot as not bonded. Unbonding
+		/// requests no longer covered by the stake being unbonded are cut.
+		///
+		/// `processed` counts the messages for `stash` the remote chain applied before the
+		/// report. While some are still on their way, the ledger kept here is ahead of the
+		/// report and only the era is recorded.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn report_remote_staking(
+			origin: OriginFor<T>,
+			era: EraIndex,
+			stash: T::AccountId,
+			ledger: Option<RemoteLedger<BalanceOf<T>>>,
+			processed: u64,
+		) -> DispatchResult {
+			T::RemoteOrigin::ensure_origin(origin)?;
+			RemoteEra::<T>::put(era);
+			if processed < RemoteMessagesSent::<T>::get(&stash) {
+				return Ok(())
+			}
+			let unlocking = ledger
+				.as_ref()
+				.map_or_else(Zero::zero, |ledger| ledger.total.saturating_sub(ledger.active));
+			RemoteLedgers::<T>::mutate(&stash, |known| *known = ledger);
+
+			for pool_id in Pools::<T>::iter_keys() {
+				if Self::pot_account(pool_id) == stash {
+					Self::cover_unbonding(pool_id, unlocking);
+					Self::note_pot_ledger(pool_id);
+				}
+			}
+			Ok(())
+		}
+
+		/// Put `pool_id` in emergency exit, which can not be undone.
+		///
+		/// The pot stops nominating and unbonds all of its stake. No more liquid currency is
+		/// minted, and holders redeem their share of the stake with `emergency_redeem` as it
+		/// unlocks.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(5, 5))]
+		#[transactional]
+		pub fn enable_emergency_exit(origin: OriginFor<T>, pool_id: PoolId) -> DispatchResult {
+			T::AdminOrigin::ensure_origin(origin)?;
+			Self::pool(pool_id)?;
+			ensure!(!EmergencyExit::<T>::get(pool_id), Error::<T>::EmergencyExitEnabled);
+
+			let pot_account = Self::pot_account(pool_id);
+			T::StakingBackend::chill(&pot_account)?;
+			let active = Self::active(&pot_account);
+			if !active.is_zero() {
+				T::StakingBackend::unbond(&pot_account, active)?;
+			}
+			EmergencyExit::<T>::insert(pool_id, true);
+
+			Self::deposit_event(Event::EmergencyExitEnabled { pool_id });
+			Self::note_pot_ledger(pool_id);
+			Ok(())
+		}
+
+		/// Burn `liquid_amount` of the liquid currency of `pool_id`, in emergency exit, for its
+		/// share of the pool's stake.
+		///
+		/// Thresholds and pauses do not apply, and the stake is paid as soon as the pot's stake
+		/// is unlocked. The unlocked stake owed to pending unbonding requests is left for them.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(6, 6))]
+		#[transactional]
+		pub fn emergency_redeem(
+			origin: OriginFor<T>,
+			pool_id: PoolId,
+			#[pallet::compact] liquid_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let pool = Self::pool(pool_id)?;
+			ensure!(EmergencyExit::<T>::get(pool_id), Error::<T>::EmergencyExitNotEnabled);
+			Self::ensure_non_zero(liquid_amount)?;
+			let liquid_amount = Self::to_shares(pool_id, &pool, liquid_amount)?;
+			let staking_amount = Self::liquid_to_staking(pool_id, liquid_amount)?;
+			Self::ensure_non_zero(staking_amount)?;
+			Self::ensure_can_send(pool.liquid_currency_id, &who, liquid_amount)?;
+			Self::ensure_can_receive(T::StakingCurrencyId::get(), &who, staking_amount)?;
+
+			let pot_account = Self::pot_account(pool_id);
+			let withdrawn = Self::withdraw_pot(&pot_account)?;
+			let unclaimed = Unclaimed::<T>::get(pool_id).saturating_add(withdrawn);
+			// the stake owed to pending unbonding requests is not for redemption
+			let redeemable = unclaimed.saturating_sub(PendingUnbonding::<T>::get(pool_id).0);
+			ensure!(staking_amount <= redeemable, Error::<T>::EmergencyFundsLocked);
+			Self::ensure_can_send(T::StakingCurrencyId::get(), &pot_account, staking_amount)?;
+			Unclaimed::<T>::insert(pool_id, unclaimed.saturating_sub(staking_amount));
+
+			<T as pallet::Config>::Currency::withdraw(
+				pool.liquid_currency_id,
+				&who,
+				liquid_amount,
+			)?;
+			TotalLiquidIssuance::<T>::mutate(pool_id, |total| {
+				*total = total.saturating_sub(liquid_amount)
+			});
+			<T as pallet::Config>::Currency::transfer(
+				T::StakingCurrencyId::get(),
+				&pot_account,
+				&who,
+				staking_amount,
+			)?;
+
+			Self::deposit_event(Event::EmergencyRedeemed {
+				pool_id,
+				who,
+				liquid_amount,
+				staking_amount,
+			});
+			Self::note_pot_ledger(pool_id);
+			Ok(())
+		}
+
+		/// Record `validator` as a validator of the chain `backend::RemoteStaking` stakes on,
+		/// as of its era `era`.
+		///
+		/// `None` reports that it stopped validating. Approvals, scores and the checks of the
+		/// pots' nominations use these reports.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(2))]
+		pub fn report_remote_validator(
+			origin: OriginFor<T>,
+			era: EraIndex,
+			validator: T::AccountId,
+			status: Option<ValidatorStatus<BalanceOf<T>>>,
+		) -> DispatchResult {
+			T::RemoteOrigin::ensure_origin(origin)?;
+			RemoteEra::<T>::put(era);
+			RemoteValidators::<T>::mutate(&validator, |known| *known = status);
+			Ok(())
+		}
+	}
+
+	#[pallet::validate_unsigned]
+	impl<T: Config> ValidateUnsigned for Pallet<T>
+	where
+		BalanceOf<T>: FixedPointOperand,
+	{
+		type Call = Call<T>;
+
+		fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+			if let Call::report_rate_drop { pool_id } = call {
+				let (era, ..) = Self::rate_drop(*pool_id).ok_or(InvalidTransaction::Stale)?;
+				ValidTransaction::with_tag_prefix("LiquidStakingRateDrop")
+					.priority(T::UnsignedPriority::get())
+					.and_provides((pool_id, era))
+					.longevity(64)
+					.propagate(true)
+					.build()
+			} else {
+				InvalidTransaction::Call.into()
+			}
+		}
+	}
+
+	impl<T: Config> Pallet<T>
+	where
+		BalanceOf<T>: FixedPointOperand,
+	{
+		/// Pot account of pool `pool_id`
+		pub fn pot_account(pool_id: PoolId) -> T::AccountId {
+			T::PalletId::get().into_sub_account_truncating(pool_id)
+		}
+
+		/// The pool `pool_id`, or `PoolNotFound` if it does not exist.
+		pub fn pool(pool_id: PoolId) -> Result<PoolInfo<BalanceOf<T>>, DispatchError> {
+			Pools::<T>::get(pool_id).ok_or_else(|| Error::<T>::PoolNotFound.into())
+		}
+
+		fn do_withdraw_unbonded(pool_id: PoolId, claim_id: ClaimId) -> DispatchResult {
+			let pool = Self::pool(pool_id)?;
+			// Get the unbonding request
+			let unbonding_request = UnbondingRequests::<T>::get(pool_id, claim_id);
+			let who = ClaimOwner::<T>::get(pool_id, claim_id);
+
+			ensure!(
+				unbonding_request.is_some() && who.is_some(),
+				Error::<T>::UnbondingRequestNotExist
+			);
+			let UnbondingRequest { staking_amount: stake_amount, liquid_amount, era: old_era } =
+				unbonding_request.unwrap();
+			let who = who.unwrap();
+
+			let current_era = Self::current_era();
+			ensure!(current_era.is_some(), Error::<T>::CurrentEraNotSet);
+
+			let unbond_wait = UnbondWait::<T>::get();
+
+			ensure!(
+				old_era + unbond_wait <= current_era.unwrap(),
+				Error::<T>::UnbondingWaitNotComplete
+			);
+
+			let pot_account = Self::pot_account(pool_id);
+			// matured chunks of every request are withdrawn, they back the pool until paid
+			let withdrawn = Self::withdraw_pot(&pot_account)?;
+			Unclaimed::<T>::mutate(pool_id, |unclaimed| {
+				*unclaimed = unclaimed.saturating_add(withdrawn).saturating_sub(stake_amount)
+			});
+
+			Self::ensure_can_send(T::StakingCurrencyId::get(), &pot_account, stake_amount)?;
+			Self::ensure_can_receive(T::StakingCurrencyId::get(), &who, stake_amount)?;
 
+			// burn liquid amount
+			<T as pallet::Config>::Currency::withdraw(
+				pool.liquid_currency_id,
+				&pot_account,
+				liquid_amount,
+			)?;
+
+			// transfer redeemed_staking to redeemer.
+			<T as pallet::Config>::Currency::transfer(
+				T::StakingCurrencyId::get(),
+				&pot_account,
+				&who,
+				stake_amount,
+			)?;
+
+			UnbondingRequests::<T>::remove(pool_id, claim_id);
+			ClaimOwner::<T>::remove(pool_id, claim_id);
+			Self::remove_claim(&who);
+			Self::dequeue_unbonding(pool_id, claim_id);
+			Self::release_pending(pool_id, stake_amount, liquid_amount);
+			TotalLiquidIssuance::<T>::mutate(pool_id, |total| {
+				*total = total.saturating_sub(liquid_amount)
+			});
+
+			// Emit an event.
+			Self::deposit_event(Event::Withdraw {
+				pool_id,
+				claim_id,
+				who,
+				staking_amount: stake_amount,
+				liquid_amount,
+				era: current_era.unwrap(),
+			});
+			Self::note_pot_ledger(pool_id);
+			// Return a successful result
+			Ok(())
+		}
+
+		/// Take a request for `staking_amount` and `liquid_amount` out of the pending unbonding of
+		/// `pool_id`.
+		fn release_pending(
+			pool_id: PoolId,
+			staking_amount: BalanceOf<T>,
+			liquid_amount: BalanceOf<T>,
+		) {
+			PendingUnbonding::<T>::mutate(pool_id, |(staking, liquid)| {
+				*staking = staking.saturating_sub(staking_amount);
+				*liquid = liquid.saturating_sub(liquid_amount);
+			});
+		}
+
+		/// Cut the unbonding requests of `pool_id` pro rata so they are covered by the pot's
+		/// `unlocking` stake and `Unclaimed`, after a slash of its unlocking stake.
+		///
+		/// Requests are not tied to the chunks they unbonded, so the slash is shared by all of
+		/// them, like a slash of the active stake is shared by the liquid currency.
+		fn cover_unbonding(pool_id: PoolId, unlocking: BalanceOf<T>) {
+			let available = unlocking.saturating_add(Unclaimed::<T>::get(pool_id));
+			let (pending, pending_liquid) = PendingUnbonding::<T>::get(pool_id);
+			if pending <= available {
+				return
+			}
+
+			let mut remaining: BalanceOf<T> = Zero::zero();
+			for (claim_id, mut request) in
+				UnbondingRequests::<T>::iter_prefix(pool_id).collect::<Vec<_>>()
+			{
+				request.staking_amount = conversion::mul_div(
+					request.staking_amount,
+					available.saturated_into(),
+					pending.saturated_into(),
+					Rounding::Down,
+				)
+				.unwrap_or_else(|_| Zero::zero());
+				remaining = remaining.saturating_add(request.staking_amount);
+				UnbondingRequests::<T>::insert(pool_id, claim_id, request);
+			}
+			PendingUnbonding::<T>::insert(pool_id, (remaining, pending_liquid));
+			Self::deposit_event(Event::UnbondingRequestsSlashed { pool_id, pending, remaining });
+		}
+
+		/// Count a new claim of `who`, up to `Config::MaxUnbondingRequests`.
+		pub(crate) fn add_claim(who: &T::AccountId) -> DispatchResult {
+			UnbondingRequestCount::<T>::try_mutate(who, |count| -> DispatchResult {
+				ensure!(
+					*count < T::MaxUnbondingRequests::get(),
+					Error::<T>::TooManyUnbondingRequests
+				);
+				*count += 1;
+				Ok(())
+			})
+		}
+
+		/// Stop counting a claim of `who`.
+		pub(crate) fn remove_claim(who: &T::AccountId) {
+			UnbondingRequestCount::<T>::mutate_exists(who, |count| {
+				*count = count.map(|count| count.saturating_sub(1)).filter(|count| *count > 0)
+			});
+		}
+
+		fn dequeue_unbonding(pool_id: PoolId, claim_id: ClaimId) {
+			UnbondingQueue::<T>::mutate(|queue| {
+				queue.retain(|queued| *queued != (pool_id, claim_id))
+			});
+		}
+
+		/// Weight of paying out one unbonding request.
+		pub(crate) fn withdraw_weight() -> Weight {
+			T::DbWeight::get().reads_writes(10, 10)
+		}
+
+		/// Pay out completed unbonding requests in the order they were made, within
+		/// `remaining_weight`.
+		fn sweep_unbonded(remaining_weight: Weight) -> Weight {
+			let mut used = T::DbWeight::get().reads(2);
+			if used > remaining_weight {
+				return 0
+			}
+			let current_era = match Self::current_era() {
+				Some(era) => era,
+				None => return used,
+			};
+
+			for (pool_id, claim_id) in UnbondingQueue::<T>::get() {
+				if used.saturating_add(Self::withdraw_weight()) > remaining_weight {
+					break
+				}
+				let request = match UnbondingRequests::<T>::get(pool_id, claim_id) {
+					Some(request) => request,
+					None => {
+						used = used.saturating_add(T::DbWeight::get().reads_writes(1, 1));
+						Self::dequeue_unbonding(pool_id, claim_id);
+						continue
+					},
+				};
+				// later requests complete later
+				if request.era.saturating_add(UnbondWait::<T>::get()) > current_era {
+					break
+				}
+				if Paused::<T>::get(pool_id).contains(PauseFlags::WITHDRAW) {
+					continue
+				}
+
+				used = used.saturating_add(Self::withdraw_weight());
+				if let Err(e) =
+					with_storage_layer(|| Self::do_withdraw_unbonded(pool_id, claim_id))
+				{
+					log::warn!(
+						target: LOG_TARGET,
+						"failed to pay out unbonding {} in pool {}: {:?}",
+						claim_id,
+						pool_id,
+						e,
+					);
+					// the request stays queued and is tried again in the next idle block
+				}
+			}
+
+			used
+		}
+
+		fn ensure_non_zero(amount: BalanceOf<T>) -> DispatchResult {
+			ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+			Ok(())
+		}
+
+		/// Ensure `who` is left with none or at least the existential deposit of `currency_id`
+		/// once `amount` leaves their free balance.
+		fn ensure_can_send(
+			currency_id: CurrencyId,
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let left = <T as pallet::Config>::Currency::free_balance(currency_id, who)
+				.saturating_sub(amount);
+			Self::ensure_existential(currency_id, left)
+		}
+
+		/// Ensure `who` holds at least the existential deposit of `currency_id` once it receives
+		/// `amount`.
+		fn ensure_can_receive(
+			currency_id: CurrencyId,
+			who: &T::AccountId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let total = <T as pallet::Config>::Currency::total_balance(currency_id, who)
+				.saturating_add(amount);
+			Self::ensure_existential(currency_id, total)
+		}
+
+		fn ensure_existential(currency_id: CurrencyId, balance: BalanceOf<T>) -> DispatchResult {
+			ensure!(
+				balance.is_zero() ||
+					balance >= <T as pallet::Config>::Currency::minimum_balance(currency_id),
+				Error::<T>::BelowExistentialDeposit
+			);
+			Ok(())
+		}
+
+		/// Ensure bonding `staking_amount` for `liquid_amount` keeps `pool_id` within its deposit
+		/// caps.
+		fn ensure_within_caps(
+			pool_id: PoolId,
+			pool: &PoolInfo<BalanceOf<T>>,
+			staker: &T::AccountId,
+			staking_amount: BalanceOf<T>,
+			liquid_amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let caps = Caps::<T>::get(pool_id);
+			if let Some(max_stake) = caps.max_stake {
+				ensure!(
+					Self::backing(pool_id).saturating_add(staking_amount) <= max_stake,
+					Error::<T>::StakeCapExceeded
+				);
+			}
+
+			let era = Self::current_era().unwrap_or_default();
+			let (deposits_era, deposits) = EraDeposits::<T>::get(pool_id);
+			let deposits = if deposits_era == era { deposits } else { Zero::zero() };
+			if let Some(max_era_deposits) = caps.max_era_deposits {
+				ensure!(
+					deposits.saturating_add(staking_amount) <= max_era_deposits,
+					Error::<T>::EraDepositCapExceeded
+				);
+			}
+
+			if let Some(max_account_liquid) = caps.max_account_liquid {
+				let held = <T as pallet::Config>::Currency::total_balance(
+					pool.liquid_currency_id,
+					staker,
+				);
+				ensure!(
+					Self::from_shares(pool_id, pool, held.saturating_add(liquid_amount))? <=
+						max_account_liquid,
+					Error::<T>::AccountLiquidCapExceeded
+				);
+			}
+			Ok(())
+		}
+
+		/// Count `staking_amount` bonded with `pool_id` in the deposits of the current era.
+		fn note_deposit(pool_id: PoolId, staking_amount: BalanceOf<T>) {
+			let era = Self::current_era().unwrap_or_default();
+			EraDeposits::<T>::mutate(pool_id, |(deposits_era, deposits)| {
+				if *deposits_era != era {
+					*deposits_era = era;
+					*deposits = Zero::zero();
+				}
+				*deposits = deposits.saturating_add(staking_amount);
+			});
+		}
+
+		/// Ensure `operation` is not paused in `pool_id`, and the pool is not in emergency exit.
+		fn ensure_active(pool_id: PoolId, operation: PauseFlags) -> DispatchResult {
+			ensure!(!Paused::<T>::get(pool_id).contains(operation), Error::<T>::Paused);
+			ensure!(!EmergencyExit::<T>::get(pool_id), Error::<T>::EmergencyExitEnabled);
+			Ok(())
+		}
+
+		/// Ensure the current block is not after `deadline`, if any.
+		fn ensure_before(deadline: Option<BlockNumberFor<T>>) -> DispatchResult {
+			ensure!(
+				deadline.map_or(true, |last| frame_system::Pallet::<T>::block_number() <= last),
+				Error::<T>::DeadlinePassed
+			);
+			Ok(())
+		}
+
+		/// Minimum staking amount to bond in `pool`: the pool's own, then the pallet's.
+		fn bond_threshold(pool: &PoolInfo<BalanceOf<T>>) -> BalanceOf<T> {
+			pool.bond_threshold
+				.or_else(|| Self::params().bond_threshold)
+				.unwrap_or_else(T::BondThreshold::get)
+		}
+
+		/// Minimum liquid amount to unbond from `pool`: the pool's own, then the pallet's.
+		pub fn unbond_threshold(pool: &PoolInfo<BalanceOf<T>>) -> BalanceOf<T> {
+			pool.unbond_threshold
+				.or_else(|| Self::params().unbond_threshold)
+				.unwrap_or_else(T::UnbondThreshold::get)
+		}
+
+		/// Mint rate used while `pool_id` is empty: the pool's own, then the pallet's.
+		fn default_mint_rate(pool_id: PoolId) -> MintRate {
+			Pools::<T>::get(pool_id)
+				.and_then(|pool| pool.mint_rate)
+				.or_else(|| Self::params().default_mint_rate)
+				.unwrap_or_else(T::DefaultMintRate::get)
+		}
+
+		/// Most validators that can be approved.
+		pub fn max_validator_count() -> u32 {
+			Self::params().max_validator_count.unwrap_or_else(T::MaxValidatorCount::get)
+		}
+
+		fn ensure_valid_policy(policy: &NominationPolicy) -> DispatchResult {
+			ensure!(
+				policy.targets > 0 &&
+					policy.targets <= <T as pallet_staking::Config>::MaxNominations::get(),
+				Error::<T>::InvalidNominationPolicy
+			);
+			Ok(())
+		}
+
+		/// Liquid currency minted for `staking_amount`, rounded down.
+		pub fn staking_to_liquid(
+			pool_id: PoolId,
+			staking_amount: BalanceOf<T>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			Self::staking_to_liquid_rounded(pool_id, staking_amount, Rounding::Down)
+		}
+
+		/// Staking currency redeemed for `liquid_amount`, rounded down.
 		pub fn liquid_to_staking(
+			pool_id: PoolId,
 			liquid_amount: BalanceOf<T>,
 		) -> Result<BalanceOf<T>, DispatchError> {
-			Self::current_mint_rate()
-				.reciprocal()
-				.expect("shouldn't be invalid!")
-				.checked_mul_int(liquid_amount)
-				.ok_or(DispatchError::Arithmetic(ArithmeticError::Overflow))
+			Self::liquid_to_staking_rounded(pool_id, liquid_amount, Rounding::Down)
 		}
 
-		/// Calculate mint rate
-		/// total_liquid_amount / total_staking_amount
-		/// If mint rate cannot be calculated, T::DefaultMintRate is used.
-		pub fn current_mint_rate() -> MintRate {
-			let total_staking = <T as pallet::Config>::Currency::total_balance(
-				T::StakingCurrencyId::get(),
-				&Self::account_id(),
+		fn staking_to_liquid_rounded(
+			pool_id: PoolId,
+			staking_amount: BalanceOf<T>,
+			rounding: Rounding,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let (total_liquid, total_staking) = Self::exchange_rate(pool_id);
+			Ok(conversion::mul_div(staking_amount, total_liquid, total_staking, rounding)?)
+		}
+
+		fn liquid_to_staking_rounded(
+			pool_id: PoolId,
+			liquid_amount: BalanceOf<T>,
+			rounding: Rounding,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			let (total_liquid, total_staking) = Self::exchange_rate(pool_id);
+			Ok(conversion::mul_div(liquid_amount, total_staking, total_liquid, rounding)?)
+		}
+
+		/// Shares of the pot of `pool_id` worth `amount` of its liquid currency, rounded up as
+		/// the shares are charged.
+		///
+		/// Amounts of the liquid currency of rebasing pools are in staking terms, while the
+		/// pallet always accounts in shares.
+		pub fn to_shares(
+			pool_id: PoolId,
+			pool: &PoolInfo<BalanceOf<T>>,
+			amount: BalanceOf<T>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			match pool.accounting_mode {
+				AccountingMode::ExchangeRate => Ok(amount),
+				AccountingMode::Rebasing =>
+					Self::staking_to_liquid_rounded(pool_id, amount, Rounding::Up),
+			}
+		}
+
+		/// Amount of the liquid currency of `pool_id` that `shares` are worth, rounded down.
+		pub fn from_shares(
+			pool_id: PoolId,
+			pool: &PoolInfo<BalanceOf<T>>,
+			shares: BalanceOf<T>,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			match pool.accounting_mode {
+				AccountingMode::ExchangeRate => Ok(shares),
+				AccountingMode::Rebasing => Self::liquid_to_staking(pool_id, shares),
+			}
+		}
+
+		/// Staking currency backing the liquid currency of `pool_id` outside of pending
+		/// unbonding requests.
+		///
+		/// This is what the pot has bonded, including rewards restaked by staking and less
+		/// slashes, plus what was withdrawn for requests not paid out yet, less what the pending
+		/// requests are owed. Staking currency sent to the pot directly is not counted, so it can
+		/// not move the exchange rate.
+		pub fn backing(pool_id: PoolId) -> BalanceOf<T> {
+			Self::bonded(&Self::pot_account(pool_id))
+				.saturating_add(Self::unclaimed(pool_id))
+				.saturating_sub(PendingUnbonding::<T>::get(pool_id).0)
+		}
+
+		fn bonded(pot_account: &T::AccountId) -> BalanceOf<T> {
+			T::StakingBackend::ledger(pot_account).map_or_else(Zero::zero, |ledger| ledger.total)
+		}
+
+		/// Withdraw the stake of `pot_account` that finished unbonding, returning how much.
+		fn withdraw_pot(pot_account: &T::AccountId) -> Result<BalanceOf<T>, DispatchError> {
+			let bonded = Self::bonded(pot_account);
+			// a pot that unbonded everything has no ledger left to withdraw from
+			if T::StakingBackend::ledger(pot_account).is_some() {
+				T::StakingBackend::withdraw_unbonded(pot_account)?;
+			}
+			Ok(bonded.saturating_sub(Self::bonded(pot_account)))
+		}
+
+		fn active(pot_account: &T::AccountId) -> BalanceOf<T> {
+			T::StakingBackend::ledger(pot_account).map_or_else(Zero::zero, |ledger| ledger.active)
+		}
+
+		/// Report the ledger of the pot of `pool_id` after the pallet changed it.
+		fn note_pot_ledger(pool_id: PoolId) {
+			let (bonded, active) = T::StakingBackend::ledger(&Self::pot_account(pool_id))
+				.map_or_else(Default::default, |ledger| (ledger.total, ledger.active));
+			let unclaimed = Unclaimed::<T>::get(pool_id);
+			Self::deposit_event(Event::PotLedgerUpdated { pool_id, bonded, active, unclaimed });
+		}
+
+		/// Check that every pool is solvent and its accounting matches the currencies.
+		///
+		/// - The issuance tracked for the liquid and wrapped currencies is their total issuance.
+		/// - The staking currency withdrawn for unbonding requests is held by the pot, and with
+		///   what is still unlocking covers every pending request.
+		/// - Every pending request has an owner, and `PendingUnbonding` adds the requests up.
+		/// - `UnbondingRequestCount` counts the claims each account owns.
+		/// - In `VotingMode::Reserve`, the liquid currency reserved by each voter is what
+		///   `Voters` records.
+		#[cfg(any(feature = "try-runtime", feature = "fuzz", test))]
+		pub fn sanity_check() -> Result<(), &'static str> {
+			for (pool_id, pool) in Pools::<T>::iter() {
+				ensure!(
+					TotalLiquidIssuance::<T>::get(pool_id) ==
+						<T as pallet::Config>::Currency::total_issuance(pool.liquid_currency_id),
+					"liquid issuance of a pool does not match the currency"
+				);
+				if let Some(wrapped_currency_id) = pool.wrapped_currency_id {
+					ensure!(
+						TotalWrappedIssuance::<T>::get(pool_id) ==
+							<T as pallet::Config>::Currency::total_issuance(wrapped_currency_id),
+						"wrapped issuance of a pool does not match the currency"
+					);
+				}
+
+				let pot_account = Self::pot_account(pool_id);
+				let (bonded, active) = T::StakingBackend::ledger(&pot_account)
+					.map_or_else(Default::default, |ledger| (ledger.total, ledger.active));
+				let unclaimed = Unclaimed::<T>::get(pool_id);
+				let unlocked = <T as pallet::Config>::Currency::free_balance(
+					T::StakingCurrencyId::get(),
+					&pot_account,
+				)
+				.saturating_sub(T::StakingBackend::locked(&pot_account));
+				ensure!(unclaimed <= unlocked, "withdrawn stake is not held by the pot");
+
+				let mut pending: BalanceOf<T> = Zero::zero();
+				let mut pending_liquid: BalanceOf<T> = Zero::zero();
+				for (claim_id, request) in UnbondingRequests::<T>::iter_prefix(pool_id) {
+					ensure!(
+						ClaimOwner::<T>::contains_key(pool_id, claim_id),
+						"unbonding request without an owner"
+					);
+					pending = pending.saturating_add(request.staking_amount);
+					pending_liquid = pending_liquid.saturating_add(request.liquid_amount);
+				}
+				ensure!(
+					PendingUnbonding::<T>::get(pool_id) == (pending, pending_liquid),
+					"pending unbonding does not match the requests"
+				);
+				ensure!(
+					bonded.saturating_sub(active).saturating_add(unclaimed) >= pending,
+					"unbonding requests are not covered by the pot"
+				);
+
+				for (voter, votes) in Voters::<T>::iter_prefix(pool_id) {
+					let reserved = <T as pallet::Config>::Currency::reserved_balance(
+						pool.liquid_currency_id,
+						&voter,
+					);
+					ensure!(reserved == votes, "reserved votes do not match the voter's reserve");
+				}
+			}
+
+			let mut claims = BTreeMap::<T::AccountId, u32>::new();
+			for owner in ClaimOwner::<T>::iter_values() {
+				*claims.entry(owner).or_default() += 1;
+			}
+			ensure!(
+				UnbondingRequestCount::<T>::iter().collect::<BTreeMap<_, _>>() == claims,
+				"claims counted for an account do not match the claims it owns"
 			);
-			let total_liquid = Self::total_liquid_issuance();
+			Ok(())
+		}
+
+		/// Exchange rate of `pool_id` as (total liquid, total staking), both outside of pending
+		/// unbonding requests.
+		///
+		/// While either is zero, the default mint rate of the pool is used.
+		fn exchange_rate(pool_id: PoolId) -> (u128, u128) {
+			let total_staking = Self::backing(pool_id);
+			let total_liquid = Self::total_liquid_issuance(pool_id)
+				.saturating_sub(PendingUnbonding::<T>::get(pool_id).1);
 			if total_liquid.is_zero() || total_staking.is_zero() {
-				T::DefaultMintRate::get()
+				let default_rate = Self::default_mint_rate(pool_id);
+				(default_rate.into_inner(), MintRate::accuracy())
 			} else {
-				MintRate::checked_from_rational(total_liquid, total_staking)
-					.unwrap_or_else(T::DefaultMintRate::get)
+				(total_liquid.saturated_into(), total_staking.saturated_into())
 			}
 		}
 
+		/// Calculate mint rate of pool `pool_id`
+		/// total_liquid_amount / total_staking_amount
+		/// If mint rate cannot be calculated, the default mint rate of the pool is used.
+		pub fn current_mint_rate(pool_id: PoolId) -> MintRate {
+			let (total_liquid, total_staking) = Self::exchange_rate(pool_id);
+			MintRate::checked_from_rational(total_liquid, total_staking)
+				.unwrap_or_else(|| Self::default_mint_rate(pool_id))
+		}
+
+		/// Current era of the staking backend.
 		pub fn current_era() -> Option<EraIndex> {
-			pallet_staking::Pallet::<T>::current_era()
+			T::StakingBackend::current_era()
+		}
+
+		/// Ensure `target` can receive votes from liquid holders.
+		pub fn ensure_votable(target: &T::AccountId) -> DispatchResult {
+			ensure!(!BlockedValidators::<T>::contains_key(target), Error::<T>::ValidatorBlocked);
+			ensure!(
+				ApprovedValidators::<T>::contains_key(target),
+				Error::<T>::ValidatorNotApproved
+			);
+			let status = T::StakingBackend::validator(target).ok_or(Error::<T>::NotAValidator)?;
+			ensure!(Self::commission_within_cap(status.commission), Error::<T>::CommissionTooHigh);
+			Ok(())
+		}
+
+		/// Whether the commission of `validator` is within `MaxCommission`.
+		fn commission_allowed(validator: &T::AccountId) -> bool {
+			T::StakingBackend::validator(validator)
+				.map_or(true, |status| Self::commission_within_cap(status.commission))
+		}
+
+		/// Whether `commission` is within `MaxCommission`.
+		fn commission_within_cap(commission: Perbill) -> bool {
+			MaxCommission::<T>::get().map_or(true, |max_commission| commission <= max_commission)
+		}
+
+		/// Remove approved validators that stopped being validators in staking.
+		fn prune_validators() {
+			let chilled = ApprovedValidators::<T>::iter_keys()
+				.filter(|validator| T::StakingBackend::validator(validator).is_none())
+				.collect::<Vec<_>>();
+
+			for validator in chilled {
+				ApprovedValidators::<T>::remove(&validator);
+				ValidatorScores::<T>::remove(&validator);
+				Self::deposit_event(Event::ValidatorAutoRemoved {
+					validator,
+					reason: RemovalReason::Chilled,
+				});
+			}
+		}
+
+		/// The era, mint rate snapshot and current mint rate of `pool_id` if the rate rose beyond
+		/// `Config::RateDropThreshold` since the snapshot, and bonding is not paused yet.
+		pub fn rate_drop(pool_id: PoolId) -> Option<(EraIndex, MintRate, MintRate)> {
+			if Paused::<T>::get(pool_id).contains(PauseFlags::BOND) ||
+				EmergencyExit::<T>::get(pool_id)
+			{
+				return None
+			}
+			let (era, snapshot) = EraMintRate::<T>::get(pool_id)?;
+			let rate = Self::current_mint_rate(pool_id);
+			// the backing of a liquid unit fell by 1 - snapshot / rate
+			let kept = MintRate::from(T::RateDropThreshold::get().left_from_one());
+			(snapshot < rate.saturating_mul(kept)).then(|| (era, snapshot, rate))
+		}
+
+		/// Record the mint rate of every pool once per era, reporting the pools whose rate is not
+		/// the one recorded in the previous era.
+		fn record_mint_rates() -> Weight {
+			let era = match Self::current_era() {
+				Some(era) => era,
+				None => return T::DbWeight::get().reads(1),
+			};
+			if MintRateEra::<T>::get() == Some(era) {
+				return T::DbWeight::get().reads(2)
+			}
+			MintRateEra::<T>::put(era);
+
+			let mut pools = 0;
+			for pool_id in Pools::<T>::iter_keys() {
+				pools += 1;
+				let rate = Self::current_mint_rate(pool_id);
+				let previous = EraMintRate::<T>::get(pool_id).map(|(_, rate)| rate);
+				EraMintRate::<T>::insert(pool_id, (era, rate));
+				Self::deposit_event(Event::MintRateRecorded { pool_id, era, rate });
+				if previous != Some(rate) {
+					Self::deposit_event(Event::MintRateChanged { pool_id, era, previous, rate });
+				}
+			}
+			T::DbWeight::get().reads_writes(3 + 6 * pools, 1 + pools)
+		}
+
+		/// Drop nominated validators that were chilled, or slashed after being nominated.
+		fn check_nominations() -> Weight {
+			let mut reads = 1;
+			let mut offenders = BTreeMap::<T::AccountId, RemovalReason>::new();
+
+			for pool_id in Pools::<T>::iter_keys() {
+				reads += 1;
+				let pot_account = Self::pot_account(pool_id);
+				let nominations = match T::StakingBackend::nominations(&pot_account) {
+					Some(nominations) => nominations,
+					None => continue,
+				};
+
+				reads += backend::VALIDATOR_CHECK_READS * nominations.targets.len() as u64;
+				for target in nominations.targets.iter() {
+					if let Some(reason) = Self::nomination_issue(target, nominations.submitted_in) {
+						// a slash seen by any pot outweighs a commission above the cap
+						offenders
+							.entry(target.clone())
+							.and_modify(|known| {
+								if *known == RemovalReason::CommissionAboveCap {
+									*known = reason
+								}
+							})
+							.or_insert(reason);
+					}
+				}
+			}
+
+			let writes = 2 * offenders.len() as u64;
+			for (offender, reason) in offenders {
+				match reason {
+					// the validator may lower its commission again, so it stays approved
+					RemovalReason::CommissionAboveCap =>
+						Pools::<T>::iter_keys()
+							.for_each(|pool_id| Self::denominate(pool_id, &offender, reason)),
+					_ => Self::remove_offender(&offender, reason),
+				}
+			}
+
+			T::DbWeight::get().reads_writes(reads, writes)
+		}
+
+		/// Why `target`, nominated in era `submitted_in`, should not be nominated anymore.
+		fn nomination_issue(
+			target: &T::AccountId,
+			submitted_in: EraIndex,
+		) -> Option<RemovalReason> {
+			match T::StakingBackend::validator_check(target) {
+				None => Some(RemovalReason::Chilled),
+				Some((_, Some(slash_era))) if slash_era > submitted_in =>
+					Some(RemovalReason::Slashed),
+				Some((commission, _)) if !Self::commission_within_cap(commission) =>
+					Some(RemovalReason::CommissionAboveCap),
+				Some(_) => None,
+			}
+		}
+
+		/// Take `offender` out of the approved set and stop nominating it in every pool.
+		pub(crate) fn remove_offender(offender: &T::AccountId, reason: RemovalReason) {
+			if ApprovedValidators::<T>::take(offender).is_some() {
+				ValidatorScores::<T>::remove(offender);
+				Self::deposit_event(Event::ValidatorAutoRemoved {
+					validator: offender.clone(),
+					reason,
+				});
+			}
+			Pools::<T>::iter_keys().for_each(|pool_id| Self::denominate(pool_id, offender, reason));
+		}
+
+		/// Whether the pot of some pool nominates `target`.
+		fn is_nominated(target: &T::AccountId) -> bool {
+			Pools::<T>::iter_keys().any(|pool_id| {
+				T::StakingBackend::nominations(&Self::pot_account(pool_id))
+					.map_or(false, |nominations| nominations.targets.contains(target))
+			})
+		}
+
+		/// Nominate the current targets of the pot of `pool_id` except `offender`.
+		fn denominate(pool_id: PoolId, offender: &T::AccountId, reason: RemovalReason) {
+			let pot_account = Self::pot_account(pool_id);
+			let targets = match T::StakingBackend::nominations(&pot_account) {
+				Some(nominations) if nominations.targets.contains(offender) => nominations.targets,
+				_ => return,
+			};
+
+			let remaining =
+				targets.into_iter().filter(|target| target != offender).collect::<Vec<_>>();
+			let result = if remaining.is_empty() {
+				T::StakingBackend::chill(&pot_account)
+			} else {
+				T::StakingBackend::nominate(&pot_account, remaining)
+			};
+
+			match result {
+				Ok(()) => Self::deposit_event(Event::NominationRemoved {
+					pool_id,
+					validator: offender.clone(),
+					reason,
+				}),
+				Err(e) => log::warn!(
+					target: LOG_TARGET,
+					"pool {} failed to stop nominating {:?}: {:?}",
+					pool_id,
+					offender,
+					e,
+				),
+			}
+		}
+
+		/// Refresh the score of every approved validator.
+		fn update_scores() {
+			let active_era = T::StakingBackend::active_era().unwrap_or_default();
+			let validators = ApprovedValidators::<T>::iter_keys()
+				.filter_map(|validator| {
+					T::StakingBackend::validator(&validator).map(|status| (validator, status))
+				})
+				.collect::<Vec<_>>();
+
+			let max_points = validators
+				.iter()
+				.map(|(_, status)| status.reward_points)
+				.max()
+				.unwrap_or_default();
+			let max_self_stake = validators
+				.iter()
+				.map(|(_, status)| status.self_stake)
+				.max()
+				.unwrap_or_else(Zero::zero);
+
+			for (validator, status) in validators {
+				let slashing = match status.last_slash_era {
+					None => Perbill::one(),
+					Some(era) if era.saturating_add(UnbondWait::<T>::get()) < active_era =>
+						Perbill::from_percent(50),
+					Some(_) => Perbill::zero(),
+				};
+
+				ValidatorScores::<T>::insert(
+					&validator,
+					ValidatorScore::new(
+						share_of(status.reward_points, max_points),
+						Perbill::one().saturating_sub(status.commission),
+						slashing,
+						share_of(status.self_stake, max_self_stake),
+					),
+				);
+			}
+		}
+
+		/// Rank the approved validators by votes blended with their score and pick the best.
+		///
+		/// Votes are taken relative to the most voted validator so that both parts of the blend
+		/// are on the same scale.
+		fn select_targets(
+			votes: &BTreeMap<T::AccountId, BalanceOf<T>>,
+			policy: &NominationPolicy,
+		) -> Vec<T::AccountId> {
+			let score_weight = policy.score_weight;
+			let vote_weight = Perbill::one().saturating_sub(score_weight);
+			let max_votes = votes.values().max().copied().unwrap_or_else(Zero::zero);
+
+			let mut candidates = ApprovedValidators::<T>::iter_keys()
+				.filter(Self::commission_allowed)
+				.map(|validator| {
+					let vote_share =
+						share_of(votes.get(&validator).copied().unwrap_or_else(Zero::zero), max_votes);
+					let score = ValidatorScores::<T>::get(&validator)
+						.map_or_else(Perbill::zero, |score| score.total);
+					let weight = (vote_weight * vote_share).saturating_add(score_weight * score);
+					(validator, weight)
+				})
+				.filter(|(_, weight)| !weight.is_zero())
+				.collect::<Vec<_>>();
+
+			// highest weight first, ties are broken by account to stay deterministic
+			candidates.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+			candidates
+				.into_iter()
+				.take(policy.targets as usize)
+				.map(|(validator, _)| validator)
+				.collect()
+		}
+
+		/// Count the vote intents against the liquid balance each voter holds right now.
+		///
+		/// A voter's balance is spent on their targets one after the other, so the total weight
+		/// counted for a voter never exceeds what they hold. Intents are cleared for the next era.
+		fn tally_vote_intents(
+			pool_id: PoolId,
+			liquid_currency_id: CurrencyId,
+		) -> BTreeMap<T::AccountId, BalanceOf<T>> {
+			let mut available = BTreeMap::<T::AccountId, BalanceOf<T>>::new();
+			let mut tally = BTreeMap::<T::AccountId, BalanceOf<T>>::new();
+
+			for ((voter, target), amount) in VoteIntents::<T>::drain_prefix((pool_id,)) {
+				let balance = available.entry(voter.clone()).or_insert_with(|| {
+					<T as pallet::Config>::Currency::free_balance(liquid_currency_id, &voter)
+				});
+				let weight = amount.min(*balance);
+				*balance = balance.saturating_sub(weight);
+
+				if !weight.is_zero() {
+					let votes = tally.entry(target).or_insert_with(Zero::zero);
+					*votes = votes.saturating_add(weight);
+				}
+			}
+
+			tally
+		}
+	}
+}
+
+impl<T: Config> sp_staking::OnStakerSlash<T::AccountId, BalanceOf<T>> for Pallet<T>
+where
+	BalanceOf<T>: FixedPointOperand,
+{
+	fn on_slash(
+		stash: &T::AccountId,
+		_slashed_active: BalanceOf<T>,
+		slashed_unlocking: &BTreeMap<EraIndex, BalanceOf<T>>,
+	) {
+		// the pots are slashed through the validators they nominate, which are handled
+		// when their own ledger is slashed.
+		match Pools::<T>::iter_keys().find(|pool_id| *stash == Self::pot_account(*pool_id)) {
+			Some(pool_id) => {
+				// the stored ledger is updated after this, its chunks are still the ones slashed
+				let unlocking = pallet_staking::Ledger::<T>::get(stash)
+					.map(|ledger| {
+						ledger.unlocking.iter().fold(Zero::zero(), |unlocking: BalanceOf<T>, c| {
+							let value = slashed_unlocking.get(&c.era).copied().unwrap_or(c.value);
+							unlocking.saturating_add(value)
+						})
+					})
+					.unwrap_or_else(Zero::zero);
+				Self::cover_unbonding(pool_id, unlocking);
+			},
+			None if ApprovedValidators::<T>::contains_key(stash) || Self::is_nominated(stash) =>
+				Self::remove_offender(stash, RemovalReason::Slashed),
+			// nominators are slashed along with their validators, nothing tracks them here
+			None => {},
 		}
 	}
 }
@@ -0,0 +1,194 @@
+//! Storage migrations for the liquid staking pallet.
+
+use super::*;
+use frame_support::{
+	pallet_prelude::*,
+	traits::{GetStorageVersion, OnRuntimeUpgrade},
+};
+
+pub mod v1 {
+	use super::*;
+	use frame_support::storage_alias;
+
+	/// Liquid currency issued by the single pot of version 0.
+	#[storage_alias]
+	type TotalLiquidIssuance<T: Config> = StorageValue<Pallet<T>, BalanceOf<T>, ValueQuery>;
+
+	/// Unbonding record of version 0, one per account: (staking amount, liquid amount, era).
+	#[storage_alias]
+	type UnbondingRequests<T: Config> = StorageMap<
+		Pallet<T>,
+		Twox64Concat,
+		<T as frame_system::Config>::AccountId,
+		(BalanceOf<T>, BalanceOf<T>, EraIndex),
+	>;
+
+	/// Votes of version 0 by validator.
+	#[storage_alias]
+	type LiquidVoteCount<T: Config> = StorageMap<
+		Pallet<T>,
+		Twox64Concat,
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Liquid currency reserved by each voter of version 0.
+	#[storage_alias]
+	type Voters<T: Config> = StorageMap<
+		Pallet<T>,
+		Twox64Concat,
+		<T as frame_system::Config>::AccountId,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Turn the single pot of version 0 into pool 0.
+	///
+	/// `LiquidCurrencyId` is the liquid currency version 0 minted, its former
+	/// `Config::LiquidCurrencyId`. The pot of version 0 is the account of the pallet id, which
+	/// is also the pot of pool 0. Version 0 bonded no seed, so none is bonded for the pool.
+	pub struct MigrateToV1<T, LiquidCurrencyId>(sp_std::marker::PhantomData<(T, LiquidCurrencyId)>);
+	impl<T: Config, LiquidCurrencyId: Get<CurrencyId>> OnRuntimeUpgrade
+		for MigrateToV1<T, LiquidCurrencyId>
+	where
+		BalanceOf<T>: FixedPointOperand,
+	{
+		fn on_runtime_upgrade() -> Weight {
+			let current = Pallet::<T>::current_storage_version();
+			let onchain = Pallet::<T>::on_chain_storage_version();
+
+			if current == 1 && onchain == 0 {
+				let pool_id: PoolId = 0;
+				let liquid_currency_id = LiquidCurrencyId::get();
+				let mut reads = 1u64;
+				let mut writes = 1u64;
+
+				// version 0 nominated the two validators with the most votes
+				let nomination_policy =
+					NominationPolicy { targets: 2, score_weight: Perbill::zero() };
+				Pools::<T>::insert(
+					pool_id,
+					PoolInfo {
+						liquid_currency_id,
+						mint_rate: None,
+						bond_threshold: None,
+						unbond_threshold: None,
+						nomination_policy,
+						accounting_mode: AccountingMode::ExchangeRate,
+						wrapped_currency_id: None,
+					},
+				);
+				PoolByCurrency::<T>::insert(liquid_currency_id, pool_id);
+				NextPoolId::<T>::put(pool_id + 1);
+				super::TotalLiquidIssuance::<T>::insert(pool_id, TotalLiquidIssuance::<T>::take());
+				reads += 1;
+				writes += 4;
+
+				// the old keys share their prefix with the new ones, so they are drained first
+				let mut requests = UnbondingRequests::<T>::drain().collect::<Vec<_>>();
+				// claims are numbered in the order the requests were made
+				requests.sort_by_key(|(_, (_, _, era))| *era);
+				let mut queue = Vec::new();
+				let mut pending: (BalanceOf<T>, BalanceOf<T>) = Default::default();
+				for (claim_id, (who, (staking_amount, liquid_amount, era))) in
+					(0..).zip(requests.into_iter())
+				{
+					super::UnbondingRequests::<T>::insert(
+						pool_id,
+						claim_id,
+						UnbondingRequest { staking_amount, liquid_amount, era },
+					);
+					UnbondingRequestCount::<T>::mutate(&who, |count| *count += 1);
+					ClaimOwner::<T>::insert(pool_id, claim_id, who);
+					queue.push((pool_id, claim_id));
+					pending.0 = pending.0.saturating_add(staking_amount);
+					pending.1 = pending.1.saturating_add(liquid_amount);
+					reads += 2;
+					writes += 4;
+				}
+				NextClaimId::<T>::put(queue.len() as ClaimId);
+				PendingUnbonding::<T>::insert(pool_id, pending);
+				// requests beyond the bound are left for `withdraw_unbonded`
+				queue.truncate(T::MaxQueuedUnbondings::get() as usize);
+				UnbondingQueue::<T>::put(BoundedVec::try_from(queue).unwrap_or_default());
+
+				// version 0 withdrew every matured chunk on each payout, the stake of requests
+				// that is no longer unlocking is held by the pot
+				let pot_account = Pallet::<T>::pot_account(pool_id);
+				let unlocking = T::StakingBackend::ledger(&pot_account)
+					.map_or_else(Zero::zero, |ledger| ledger.total.saturating_sub(ledger.active));
+				Unclaimed::<T>::insert(pool_id, pending.0.saturating_sub(unlocking));
+				reads += 1;
+				writes += 4;
+
+				for (validator, votes) in LiquidVoteCount::<T>::drain().collect::<Vec<_>>() {
+					super::LiquidVoteCount::<T>::insert(pool_id, validator, votes);
+					reads += 1;
+					writes += 2;
+				}
+				for (voter, votes) in Voters::<T>::drain().collect::<Vec<_>>() {
+					super::Voters::<T>::insert(pool_id, voter, votes);
+					reads += 1;
+					writes += 2;
+				}
+				current.put::<Pallet<T>>();
+
+				log::info!(
+					target: LOG_TARGET,
+					"migrated {} unbonding requests into pool {} at storage version {:?}",
+					NextClaimId::<T>::get(),
+					pool_id,
+					current,
+				);
+				T::DbWeight::get().reads_writes(reads, writes)
+			} else {
+				log::warn!(
+					target: LOG_TARGET,
+					"MigrateToV1 should be removed, on chain storage version is {:?}",
+					onchain,
+				);
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 0,
+				"must upgrade linearly"
+			);
+			frame_support::ensure!(
+				Pallet::<T>::pot_account(0) == T::PalletId::get().into_account_truncating(),
+				"the pot of pool 0 is not the pot of version 0"
+			);
+
+			let count = UnbondingRequests::<T>::iter_keys().count() as u32;
+			Self::set_temp_storage(count, "unbonding_requests");
+			Self::set_temp_storage(TotalLiquidIssuance::<T>::get(), "total_liquid_issuance");
+			Ok(())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			use frame_support::traits::OnRuntimeUpgradeHelpersExt;
+			frame_support::ensure!(
+				Pallet::<T>::on_chain_storage_version() == 1,
+				"storage version not updated"
+			);
+
+			// every record decodes in the new layout
+			let count = super::UnbondingRequests::<T>::iter_prefix_values(0).count() as u32;
+			let prev_count = Self::get_temp_storage::<u32>("unbonding_requests").unwrap();
+			frame_support::ensure!(count == prev_count, "unbonding requests lost");
+			let issuance =
+				Self::get_temp_storage::<BalanceOf<T>>("total_liquid_issuance").unwrap();
+			frame_support::ensure!(
+				super::TotalLiquidIssuance::<T>::get(0) == issuance,
+				"liquid issuance lost"
+			);
+			Ok(())
+		}
+	}
+}
@@ -1,4 +1,11 @@
 use crate as pallet_liquid_staking;
+use crate::{
+	backend::{
+		LocalStaking, PotLedger, PotNominations, RemoteLedger, RemoteStaking,
+		SendStakingMessage, StakingBackend, StakingMessage, ValidatorStatus,
+	},
+	AccountingMode, NominationPolicy, PoolId, VotingMode,
+};
 use frame_election_provider_support::{onchain, SequentialPhragmen};
 use frame_system as system;
 use sp_core::H256;
@@ -8,14 +15,17 @@ use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup, Hash},
 	FixedPointNumber, Perbill,
 };
-use codec::{Encode};
+use codec::{Decode, Encode};
 use frame_system::{EnsureRoot, EnsureSigned};
 use pallet_democracy::{
 	conviction::Conviction,
 	vote::{AccountVote, Vote},
 };
 use primitives::MintRate;
-use sp_staking::{EraIndex, SessionIndex};
+use sp_staking::{
+	offence::{DisableStrategy, OffenceDetails, OnOffenceHandler},
+	EraIndex, SessionIndex,
+};
 
 use crate::mock::sp_api_hidden_includes_construct_runtime::hidden_include::traits::GenesisBuild;
 use frame_benchmarking::Zero;
@@ -52,7 +62,7 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		LiquidStaking: pallet_liquid_staking::{Pallet, Call, Storage, Event<T>},
+		LiquidStaking: pallet_liquid_staking::{Pallet, Call, Storage, Event<T>, ValidateUnsigned},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		Staking: pallet_staking::{Pallet, Call, Config<T>, Storage, Event<T>},
@@ -72,7 +82,7 @@ parameter_types! {
 	pub static Offset: BlockNumber = 0;
 }
 
-type AccountId = u64;
+pub type AccountId = u64;
 
 impl system::Config for Test {
 	type BaseCallFilter = frame_support::traits::Everything;
@@ -252,20 +262,27 @@ impl pallet_staking::Config for Test {
 	type GenesisElectionProvider = Self::ElectionProvider;
 	type MaxUnlockingChunks = ConstU32<32>;
 	type VoterList = pallet_staking::UseNominatorsAndValidatorsMap<Self>;
-	type OnStakerSlash = ();
+	type OnStakerSlash = LiquidStaking;
 	type BenchmarkingConfig = pallet_staking::TestBenchmarkingConfig;
 	type WeightInfo = ();
 }
 
 parameter_types! {
 	pub const StakingCurrencyId: CurrencyId = STAKING_CURRENCY_ID;
-	pub const LiquidCurrencyId: CurrencyId = LIQUID_CURRENCY_ID;
 	pub const MyPalletId: PalletId = PalletId(*b"stayquid");
 	pub DefaultMintRate: MintRate = MintRate::saturating_from_rational(10, 1);
+	pub const MinPoolSeed: Balance = 10;
 	pub const UnBondWait: EraIndex = 28;
 	pub static BondThreshold: Balance = 0;
 	pub static UnbondThreshold: Balance = 0;
 	pub static MaxValidatorCount: u32 = 5;
+	pub static MaxUnbondingRequests: u32 = 16;
+	pub static MaxQueuedUnbondings: u32 = 16;
+	pub static AutoWithdraw: bool = false;
+	pub static LiquidVotingMode: VotingMode = VotingMode::Reserve;
+	pub const RateDropThreshold: Perbill = Perbill::from_percent(10);
+	pub const UnsignedPriority: u64 = 1 << 20;
+	pub static RemoteStakingEnabled: bool = false;
 }
 
 impl pallet_liquid_staking::Config for Test {
@@ -273,11 +290,162 @@ impl pallet_liquid_staking::Config for Test {
 	type PalletId = MyPalletId;
 	type Currency = Currencies;
 	type StakingCurrencyId = StakingCurrencyId;
-	type LiquidCurrencyId = LiquidCurrencyId;
 	type DefaultMintRate = DefaultMintRate;
+	type MinPoolSeed = MinPoolSeed;
 	type BondThreshold = BondThreshold;
 	type UnbondThreshold = UnbondThreshold;
 	type MaxValidatorCount = MaxValidatorCount;
+	type MaxUnbondingRequests = MaxUnbondingRequests;
+	type MaxQueuedUnbondings = MaxQueuedUnbondings;
+	type AutoWithdraw = AutoWithdraw;
+	type VotingMode = LiquidVotingMode;
+	type RateDropThreshold = RateDropThreshold;
+	type UnsignedPriority = UnsignedPriority;
+	type AdminOrigin = EnsureRoot<AccountId>;
+	type StakingBackend = MockStakingBackend;
+	type RemoteOrigin = EnsureRoot<AccountId>;
+}
+
+/// Stakes through `MockRelay` while `RemoteStakingEnabled`, and with `Staking` otherwise.
+pub struct MockStakingBackend;
+
+macro_rules! delegate_to_enabled_backend {
+	($(fn $name:ident($($arg:ident: $ty:ty),*) -> $ret:ty;)*) => {
+		$(fn $name($($arg: $ty),*) -> $ret {
+			if RemoteStakingEnabled::get() {
+				RemoteStaking::<Test, MockRelay>::$name($($arg),*)
+			} else {
+				LocalStaking::<Test>::$name($($arg),*)
+			}
+		})*
+	};
+}
+
+impl StakingBackend<AccountId, Balance> for MockStakingBackend {
+	delegate_to_enabled_backend! {
+		fn bond(stash: &AccountId, value: Balance) -> DispatchResult;
+		fn bond_extra(stash: &AccountId, value: Balance) -> DispatchResult;
+		fn unbond(stash: &AccountId, value: Balance) -> DispatchResult;
+		fn rebond(stash: &AccountId, value: Balance) -> DispatchResult;
+		fn withdraw_unbonded(stash: &AccountId) -> DispatchResult;
+		fn nominate(stash: &AccountId, targets: Vec<AccountId>) -> DispatchResult;
+		fn chill(stash: &AccountId) -> DispatchResult;
+		fn ledger(stash: &AccountId) -> Option<PotLedger<Balance>>;
+		fn locked(stash: &AccountId) -> Balance;
+		fn nominations(stash: &AccountId) -> Option<PotNominations<AccountId>>;
+		fn current_era() -> Option<EraIndex>;
+		fn active_era() -> Option<EraIndex>;
+		fn validator(validator: &AccountId) -> Option<ValidatorStatus<Balance>>;
+		fn validator_check(validator: &AccountId) -> Option<(Perbill, Option<EraIndex>)>;
+	}
+}
+
+/// Stash of the pots on the remote chain of `MockRelay`.
+pub const REMOTE_STASH: AccountId = 999;
+
+/// Validators of the remote chain of `MockRelay`, unknown to `Staking`.
+pub const REMOTE_VALIDATORS: [AccountId; 3] = [51, 61, 71];
+
+parameter_types! {
+	/// Whether `MockRelay` holds the messages sent until `deliver_held_messages`.
+	pub static RelayHeld: bool = false;
+	/// Messages held by `MockRelay`, oldest first.
+	pub static HeldMessages: Vec<StakingMessage<AccountId, Balance>> = Vec::new();
+	/// Messages `MockRelay` delivered, which the reports count as processed.
+	pub static DeliveredMessages: u64 = 0;
+}
+
+/// Delivers staking messages right away to `Staking`, standing in for the remote chain, which
+/// stakes for the pots with `REMOTE_STASH`. The staking currency a message carries moves
+/// between the pot and `REMOTE_STASH`.
+pub struct MockRelay;
+
+impl MockRelay {
+	/// Move `value` of the staking currency carried by a message.
+	fn carry(from: &AccountId, to: &AccountId, value: Balance) -> DispatchResult {
+		<Currencies as MultiCurrency<_>>::transfer(STAKING_CURRENCY_ID, from, to, value)
+	}
+
+	fn deliver(message: StakingMessage<AccountId, Balance>) -> DispatchResult {
+		type Remote = LocalStaking<Test>;
+		DeliveredMessages::set(DeliveredMessages::get() + 1);
+		match message {
+			StakingMessage::Bond { stash, value } => {
+				Self::carry(&stash, &REMOTE_STASH, value)?;
+				Remote::bond(&REMOTE_STASH, value)
+			},
+			StakingMessage::BondExtra { stash, value } => {
+				Self::carry(&stash, &REMOTE_STASH, value)?;
+				Remote::bond_extra(&REMOTE_STASH, value)
+			},
+			StakingMessage::Unbond { value, .. } => Remote::unbond(&REMOTE_STASH, value),
+			StakingMessage::Rebond { value, .. } => Remote::rebond(&REMOTE_STASH, value),
+			StakingMessage::WithdrawUnbonded { stash } => {
+				let bonded = Remote::locked(&REMOTE_STASH);
+				Remote::withdraw_unbonded(&REMOTE_STASH)?;
+				let withdrawn = bonded - Remote::locked(&REMOTE_STASH);
+				Self::carry(&REMOTE_STASH, &stash, withdrawn)
+			},
+			StakingMessage::Nominate { targets, .. } => Remote::nominate(&REMOTE_STASH, targets),
+			StakingMessage::Chill { .. } => Remote::chill(&REMOTE_STASH),
+		}
+	}
+}
+
+impl SendStakingMessage<AccountId, Balance> for MockRelay {
+	fn send(message: StakingMessage<AccountId, Balance>) -> DispatchResult {
+		let message = StakingMessage::decode(&mut &message.encode()[..]).unwrap();
+		if RelayHeld::get() {
+			let mut held = HeldMessages::get();
+			held.push(message);
+			HeldMessages::set(held);
+			return Ok(())
+		}
+		Self::deliver(message)
+	}
+}
+
+/// Deliver the messages `MockRelay` held, in the order they were sent, and stop holding them.
+pub fn deliver_held_messages() {
+	RelayHeld::set(false);
+	for message in HeldMessages::get() {
+		assert_ok!(MockRelay::deliver(message));
+	}
+	HeldMessages::set(Vec::new());
+}
+
+/// Report the era of `Staking` and the ledger of `REMOTE_STASH` as the ledger of the pot of
+/// `pool_id`, after the messages delivered so far, as the remote chain of `MockRelay` would.
+pub fn report_remote_staking(pool_id: PoolId) {
+	let ledger = Staking::ledger(REMOTE_STASH).map(|ledger| RemoteLedger {
+		total: ledger.total,
+		active: ledger.active,
+		unlocking: ledger
+			.unlocking
+			.iter()
+			.map(|chunk| (chunk.era, chunk.value))
+			.collect::<Vec<_>>()
+			.try_into()
+			.unwrap(),
+	});
+	assert_ok!(LiquidStaking::report_remote_staking(
+		Origin::root(),
+		current_era(),
+		LiquidStaking::pot_account(pool_id),
+		ledger,
+		DeliveredMessages::get(),
+	));
+}
+
+/// Report `validator` with `status` in the era of `Staking`, as the remote chain of `MockRelay`
+/// would.
+pub fn report_remote_validator(validator: AccountId, status: Option<ValidatorStatus<Balance>>) {
+	assert_ok!(LiquidStaking::report_remote_validator(
+		Origin::root(),
+		current_era(),
+		validator,
+		status,
+	));
 }
 
 parameter_types! {
@@ -347,7 +515,7 @@ where
 	type Extrinsic = sp_runtime::testing::TestXt<Call, ()>;
 }
 
-pub(crate) fn validator_controllers() -> Vec<AccountId> {
+pub fn validator_controllers() -> Vec<AccountId> {
 	Session::validators()
 		.into_iter()
 		.map(|s| Staking::bonded(&s).expect("no controller for validator"))
@@ -355,7 +523,7 @@ pub(crate) fn validator_controllers() -> Vec<AccountId> {
 }
 
 /// Progresses from the current block number (whatever that may be) to the `P * session_index + 1`.
-pub(crate) fn start_session(session_index: SessionIndex) {
+pub fn start_session(session_index: SessionIndex) {
 	let end: u64 = if Offset::get().is_zero() {
 		(session_index as u64) * Period::get()
 	} else {
@@ -377,12 +545,13 @@ pub(crate) fn start_session(session_index: SessionIndex) {
 /// This will finalize the previous block, initialize up to the given block, essentially simulating
 /// a block import/propose process where we first initialize the block, then execute some stuff (not
 /// in the function), and then finalize the block.
-pub(crate) fn run_to_block(n: BlockNumber) {
+pub fn run_to_block(n: BlockNumber) {
 	<Staking as Hooks<u64>>::on_finalize(System::block_number());
 	for b in (System::block_number() + 1)..=n {
 		System::set_block_number(b);
 		Session::on_initialize(b);
 		<Staking as Hooks<u64>>::on_initialize(b);
+		<LiquidStaking as Hooks<u64>>::on_initialize(b);
 		Timestamp::set_timestamp(System::block_number() * BLOCK_TIME + INIT_TIMESTAMP);
 		if b != n {
 			<Staking as Hooks<u64>>::on_finalize(System::block_number());
@@ -391,7 +560,7 @@ pub(crate) fn run_to_block(n: BlockNumber) {
 }
 
 /// Progress until the given era.
-pub(crate) fn start_active_era(era_index: EraIndex) {
+pub fn start_active_era(era_index: EraIndex) {
 	start_session((era_index * <SessionsPerEra as Get<u32>>::get()).into());
 	assert_eq!(active_era(), era_index);
 	// One way or another, current_era must have changed before the active era, so they must match
@@ -399,17 +568,28 @@ pub(crate) fn start_active_era(era_index: EraIndex) {
 	assert_eq!(current_era(), active_era());
 }
 
-pub(crate) fn active_era() -> EraIndex {
+pub fn active_era() -> EraIndex {
 	Staking::active_era().unwrap().index
 }
 
-pub(crate) fn current_era() -> EraIndex {
+pub fn current_era() -> EraIndex {
 	Staking::current_era().unwrap()
 }
 
+/// Report an offence of `stash` in the active era, slashing it by `fraction` right away.
+pub fn slash_validator(stash: AccountId, fraction: Perbill) {
+	let era = active_era();
+	let _ = Staking::on_offence(
+		&[OffenceDetails { offender: (stash, Staking::eras_stakers(era, stash)), reporters: vec![] }],
+		&[fraction],
+		Staking::eras_start_session_index(era).unwrap(),
+		DisableStrategy::WhenSlashed,
+	);
+}
+
 pub type ReferendumIndex = u32;
 
-pub(crate) fn begin_referendum() -> ReferendumIndex {
+pub fn begin_referendum() -> ReferendumIndex {
 	System::set_block_number(0);
 	assert_ok!(propose_set_balance_and_note(1, 2, 1));
 	fast_forward_to(2);
@@ -449,26 +629,68 @@ fn set_balance_proposal(value: u128) -> Vec<u8> {
 }
 use pallet_democracy::MultiCurrency;
 
-pub(crate) fn aye(who: AccountId, currency_id: CurrencyId) -> AccountVote<u128> {
+pub fn aye(who: AccountId, currency_id: CurrencyId) -> AccountVote<u128> {
 	AccountVote::Standard { vote: AYE, balance: Currencies::free_balance(currency_id, &who) }
 }
 pub use pallet_staking::StakerStatus;
+
+/// The pool created by `ExtBuilder`, minting `LIQUID_CURRENCY_ID`.
+pub const POOL: PoolId = 0;
+
+/// Give the pot of `pool_id` the seed `create_pool` bonds.
+pub fn fund_pot(pool_id: PoolId) {
+	let pot_account = LiquidStaking::pot_account(pool_id);
+	assert_ok!(Currencies::deposit(STAKING_CURRENCY_ID, &pot_account, MinPoolSeed::get()));
+}
+
+/// Pay `amount` of staking rewards to the pot of `pool_id`, which restakes them.
+pub fn reward_pot(pool_id: PoolId, amount: Balance) {
+	let pot_account = LiquidStaking::pot_account(pool_id);
+	assert_ok!(Currencies::deposit(STAKING_CURRENCY_ID, &pot_account, amount));
+	assert_ok!(Staking::bond_extra(Origin::signed(pot_account), amount));
+}
+
+pub fn nomination_policy(targets: u32, score_weight: Perbill) -> NominationPolicy {
+	NominationPolicy { targets, score_weight }
+}
+
 pub struct ExtBuilder {
 	balances: Vec<(AccountId, CurrencyId, Balance)>,
+	approved_validators: Vec<AccountId>,
+	remote_staking: bool,
 }
 
 impl Default for ExtBuilder {
 	fn default() -> Self {
-		Self { balances: vec![] }.topup_balances()
+		Self { balances: vec![], approved_validators: vec![11, 21, 31], remote_staking: false }
+			.topup_balances()
 	}
 }
 
 impl ExtBuilder {
+	pub fn approved_validators(mut self, validators: Vec<AccountId>) -> Self {
+		self.approved_validators = validators;
+		self
+	}
+
 	pub fn balances(mut self, balances: Vec<(AccountId, CurrencyId, Balance)>) -> Self {
 		self.balances = balances;
 		self
 	}
 
+	/// Stake through `MockRelay` from genesis, approving `REMOTE_VALIDATORS`.
+	pub fn remote_staking(mut self) -> Self {
+		self.remote_staking = true;
+		self.approved_validators = REMOTE_VALIDATORS.to_vec();
+		self
+	}
+
+	/// Drop the liquid currency given out at genesis, so all of it is minted by the pools.
+	pub fn without_genesis_liquid(mut self) -> Self {
+		self.balances.retain(|(_, currency_id, _)| *currency_id == STAKING_CURRENCY_ID);
+		self
+	}
+
 	pub fn topup_balances(self) -> Self {
 		self.balances(vec![
 			(1, STAKING_CURRENCY_ID, 1000),
@@ -554,6 +776,30 @@ impl ExtBuilder {
 		}
 		.assimilate_storage(&mut t);
 
-		t.into()
+		let mut ext: sp_io::TestExternalities = t.into();
+		ext.execute_with(|| {
+			RemoteStakingEnabled::set(self.remote_staking);
+			if self.remote_staking {
+				report_remote_staking(POOL);
+			}
+			fund_pot(POOL);
+			assert_ok!(LiquidStaking::create_pool(
+				Origin::root(),
+				LIQUID_CURRENCY_ID,
+				None,
+				None,
+				None,
+				nomination_policy(2, Perbill::zero()),
+				AccountingMode::ExchangeRate,
+			));
+			for validator in self.approved_validators {
+				if self.remote_staking {
+					let status = ValidatorStatus { self_stake: 100, ..Default::default() };
+					report_remote_validator(validator, Some(status));
+				}
+				assert_ok!(LiquidStaking::add_validator(Origin::root(), validator));
+			}
+		});
+		ext
 	}
 }
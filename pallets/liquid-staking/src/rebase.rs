@@ -0,0 +1,135 @@
+//! A view of the liquid currencies where rebasing pools report balances as share × index.
+//!
+//! The pallet and the underlying `Config::Currency` always hold shares of the pots. Integrations
+//! that want a rebasing token (AMMs, lending) use `RebasingCurrency` as their `MultiCurrency`
+//! instead. Currencies of exchange rate pools, and any other currency, are passed through as is.
+//! Only the pallet issues and burns the currencies of the pools, so `deposit`, `withdraw` and
+//! `slash` refuse them rather than leave `TotalLiquidIssuance` behind.
+
+use crate::{AccountingMode, BalanceOf, Config, Error, Pallet, PoolId, PoolInfo, Pools};
+use frame_support::pallet_prelude::*;
+use orml_traits::MultiCurrency;
+use primitives::CurrencyId;
+use sp_runtime::{traits::Zero, FixedPointOperand};
+use sp_std::marker::PhantomData;
+
+/// The underlying currencies, holding shares of the pots.
+type Shares<T> = <T as Config>::Currency;
+
+/// `MultiCurrency` reporting the liquid currencies of rebasing pools in staking terms.
+pub struct RebasingCurrency<T>(PhantomData<T>);
+
+impl<T: Config> RebasingCurrency<T>
+where
+	BalanceOf<T>: FixedPointOperand,
+{
+	/// The pool minting `currency_id`, if it is rebasing.
+	fn rebasing_pool(currency_id: CurrencyId) -> Option<(PoolId, PoolInfo<BalanceOf<T>>)> {
+		let pool_id = Pallet::<T>::pool_of_currency(currency_id)?;
+		Pools::<T>::get(pool_id)
+			.filter(|pool| {
+				pool.liquid_currency_id == currency_id &&
+					pool.accounting_mode == AccountingMode::Rebasing
+			})
+			.map(|pool| (pool_id, pool))
+	}
+
+	/// Fails for the liquid and wrapped currencies of the pools.
+	fn ensure_not_issued_by_pool(currency_id: CurrencyId) -> DispatchResult {
+		ensure!(Pallet::<T>::pool_of_currency(currency_id).is_none(), Error::<T>::IssuedByPool);
+		Ok(())
+	}
+
+	fn to_shares(
+		currency_id: CurrencyId,
+		amount: BalanceOf<T>,
+	) -> Result<BalanceOf<T>, DispatchError> {
+		match Self::rebasing_pool(currency_id) {
+			Some((pool_id, pool)) => Pallet::<T>::to_shares(pool_id, &pool, amount),
+			None => Ok(amount),
+		}
+	}
+
+	fn from_shares(currency_id: CurrencyId, shares: BalanceOf<T>) -> BalanceOf<T> {
+		match Self::rebasing_pool(currency_id) {
+			Some((pool_id, pool)) =>
+				Pallet::<T>::from_shares(pool_id, &pool, shares).unwrap_or_else(|_| Zero::zero()),
+			None => shares,
+		}
+	}
+}
+
+impl<T: Config> MultiCurrency<T::AccountId> for RebasingCurrency<T>
+where
+	BalanceOf<T>: FixedPointOperand,
+{
+	type CurrencyId = CurrencyId;
+	type Balance = BalanceOf<T>;
+
+	fn minimum_balance(currency_id: Self::CurrencyId) -> Self::Balance {
+		Self::from_shares(currency_id, Shares::<T>::minimum_balance(currency_id))
+	}
+
+	fn total_issuance(currency_id: Self::CurrencyId) -> Self::Balance {
+		Self::from_shares(currency_id, Shares::<T>::total_issuance(currency_id))
+	}
+
+	fn total_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		Self::from_shares(currency_id, Shares::<T>::total_balance(currency_id, who))
+	}
+
+	fn free_balance(currency_id: Self::CurrencyId, who: &T::AccountId) -> Self::Balance {
+		Self::from_shares(currency_id, Shares::<T>::free_balance(currency_id, who))
+	}
+
+	fn ensure_can_withdraw(
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Shares::<T>::ensure_can_withdraw(currency_id, who, Self::to_shares(currency_id, amount)?)
+	}
+
+	fn transfer(
+		currency_id: Self::CurrencyId,
+		from: &T::AccountId,
+		to: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Shares::<T>::transfer(currency_id, from, to, Self::to_shares(currency_id, amount)?)
+	}
+
+	fn deposit(
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Self::ensure_not_issued_by_pool(currency_id)?;
+		Shares::<T>::deposit(currency_id, who, amount)
+	}
+
+	fn withdraw(
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> DispatchResult {
+		Self::ensure_not_issued_by_pool(currency_id)?;
+		Shares::<T>::withdraw(currency_id, who, amount)
+	}
+
+	fn can_slash(currency_id: Self::CurrencyId, who: &T::AccountId, value: Self::Balance) -> bool {
+		Self::ensure_not_issued_by_pool(currency_id).is_ok() &&
+			Shares::<T>::can_slash(currency_id, who, value)
+	}
+
+	fn slash(
+		currency_id: Self::CurrencyId,
+		who: &T::AccountId,
+		amount: Self::Balance,
+	) -> Self::Balance {
+		match Self::ensure_not_issued_by_pool(currency_id) {
+			Ok(()) => Shares::<T>::slash(currency_id, who, amount),
+			Err(_) => amount,
+		}
+	}
+}
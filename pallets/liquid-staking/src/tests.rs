@@ -1,17 +1,37 @@
 #![cfg(test)]
 
 use super::*;
-use frame_support::{assert_err, assert_ok};
+use frame_support::{
+	assert_err, assert_noop, assert_ok,
+	pallet_prelude::{InvalidTransaction, TransactionSource, ValidateUnsigned},
+	traits::{
+		tokens::nonfungibles::{Inspect, Transfer},
+		GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion,
+	},
+	weights::Weight,
+};
+use sp_core::offchain::{testing::TestTransactionPoolExt, TransactionPoolExt};
+use sp_runtime::{testing::TestXt, traits::BadOrigin, ArithmeticError, FixedPointNumber, Perbill};
 use mock::*;
+use pallet_staking::{ConfigOp, RewardDestination, ValidatorPrefs};
 use orml_traits::MultiReservableCurrency;
 use primitives::{LIQUID_CURRENCY_ID, STAKING_CURRENCY_ID};
 use substrate_test_utils::assert_eq_uvec;
 
+fn unbonding(
+	staking_amount: BalanceOf<Test>,
+	liquid_amount: BalanceOf<Test>,
+	era: EraIndex,
+) -> UnbondingRequest<BalanceOf<Test>> {
+	UnbondingRequest { staking_amount, liquid_amount, era }
+}
+
 #[test]
 fn total_issuance() {
 	ExtBuilder::default().build().execute_with(|| {
-		assert_eq!(Currencies::total_issuance(STAKING_CURRENCY_ID), 18400);
-		assert_eq!(Currencies::total_issuance(LIQUID_CURRENCY_ID), 2000);
+		// including the seed of the pool
+		assert_eq!(Currencies::total_issuance(STAKING_CURRENCY_ID), 18410);
+		assert_eq!(Currencies::total_issuance(LIQUID_CURRENCY_ID), 2100);
 	});
 }
 
@@ -21,70 +41,128 @@ fn bonding_works() {
 		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 1000);
 		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 0);
 
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), 200));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
 
 		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 800);
-		let pot_account = LiquidStaking::account_id();
-		let total_liquid_issuance = LiquidStaking::total_liquid_issuance();
+		let pot_account = LiquidStaking::pot_account(POOL);
+		let total_liquid_issuance = LiquidStaking::total_liquid_issuance(POOL);
 
-		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &pot_account), 200);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &pot_account), 210);
 
-		assert_eq!(total_liquid_issuance, 2000);
+		assert_eq!(total_liquid_issuance, 2100);
 
 		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 2000);
 	});
 }
 
+#[test]
+fn events_report_amounts_rates_and_the_pot_ledger() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_eq!(
+			LiquidStaking::era_mint_rate(POOL),
+			Some((1, MintRate::saturating_from_integer(10)))
+		);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::MintRateRecorded {
+			pool_id: POOL,
+			era: 1,
+			rate: MintRate::saturating_from_integer(10),
+		}));
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 100, None, None));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::BondAndMint {
+			pool_id: POOL,
+			who: 101,
+			staking_amount: 100,
+			liquid_amount: 1000,
+			rate: MintRate::saturating_from_integer(10),
+			era: 1,
+		}));
+		System::assert_last_event(mock::Event::LiquidStaking(crate::Event::PotLedgerUpdated {
+			pool_id: POOL,
+			bonded: 110,
+			active: 110,
+			unclaimed: 0,
+		}));
+
+		// rewards double the pot, the new rate is reported once the next era starts
+		reward_pot(POOL, 110);
+		start_active_era(2);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::MintRateChanged {
+			pool_id: POOL,
+			era: 2,
+			previous: Some(MintRate::saturating_from_integer(10)),
+			rate: MintRate::saturating_from_integer(5),
+		}));
+
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+		System::assert_last_event(mock::Event::LiquidStaking(crate::Event::PotLedgerUpdated {
+			pool_id: POOL,
+			bonded: 220,
+			active: 120,
+			unclaimed: 0,
+		}));
+
+		// the rate is recorded in every era, changed or not
+		start_active_era(3);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::MintRateRecorded {
+			pool_id: POOL,
+			era: 3,
+			rate: MintRate::saturating_from_integer(5),
+		}));
+	});
+}
+
 #[test]
 fn staking_to_liquid_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		assert_eq!(LiquidStaking::staking_to_liquid(10).unwrap(), 100);
+		assert_eq!(LiquidStaking::staking_to_liquid(POOL, 10).unwrap(), 100);
 	});
 }
 
 #[test]
 fn liquid_to_staking_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		assert_eq!(LiquidStaking::liquid_to_staking(1000).unwrap(), 100);
+		assert_eq!(LiquidStaking::liquid_to_staking(POOL, 1000).unwrap(), 100);
 	});
 }
 
 #[test]
 fn voting_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		let pot_account = LiquidStaking::account_id();
+		let pot_account = LiquidStaking::pot_account(POOL);
 		assert_eq!(LiquidStaking::current_era().unwrap(), 0);
 		assert_eq_uvec!(validator_controllers(), vec![20, 10]);
 		start_active_era(1);
 
 		// stakers minting
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), 200));
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), 200));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None));
 		// stakers voting for their favourite nomination
 		// highest votes
-		assert_ok!(LiquidStaking::vote(Origin::signed(101), 30, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
 		assert_eq!(
 			<Currencies as MultiReservableCurrency<_>>::reserved_balance(LIQUID_CURRENCY_ID, &101),
 			300
 		);
 		// second highest
-		assert_ok!(LiquidStaking::vote(Origin::signed(102), 20, 200));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), POOL, 21, 200));
 		// bottom
-		assert_ok!(LiquidStaking::vote(Origin::signed(102), 10, 150));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), POOL, 11, 150));
 
 		// verify vote counts
-		assert_eq!(LiquidStaking::liquid_vote_count(30), 300);
-		assert_eq!(LiquidStaking::liquid_vote_count(20), 200);
-		assert_eq!(LiquidStaking::liquid_vote_count(10), 150);
-		let expected_set = vec![30, 20];
-		assert_ok!(LiquidStaking::apply_votes(Origin::root()));
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 31), 300);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 21), 200);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 11), 150);
+		let expected_set = vec![31, 21];
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
 		assert_eq!(expected_set, Staking::nominators(&pot_account).unwrap().targets.into_inner());
 
 		// vote apply should clear votes
 		// verify vote counts
-		assert_eq!(LiquidStaking::liquid_vote_count(30), 0);
-		assert_eq!(LiquidStaking::liquid_vote_count(20), 0);
-		assert_eq!(LiquidStaking::liquid_vote_count(10), 0);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 31), 0);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 21), 0);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 11), 0);
 		// vote apply should also have unreseved the balances
 		assert_eq!(
 			<Currencies as MultiReservableCurrency<_>>::reserved_balance(LIQUID_CURRENCY_ID, &101),
@@ -94,60 +172,632 @@ fn voting_works() {
 		// Lets try to vote again
 		start_active_era(5);
 		// highest votes
-		assert_ok!(LiquidStaking::vote(Origin::signed(101), 10, 200));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 200));
 		// second highest
-		assert_ok!(LiquidStaking::vote(Origin::signed(102), 20, 180));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), POOL, 21, 180));
 		// bottom
-		assert_ok!(LiquidStaking::vote(Origin::signed(101), 30, 150));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 150));
 
-		assert_eq!(LiquidStaking::liquid_vote_count(10), 200);
-		assert_eq!(LiquidStaking::liquid_vote_count(20), 180);
-		assert_eq!(LiquidStaking::liquid_vote_count(30), 150);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 11), 200);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 21), 180);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 31), 150);
 
-		let expected_set = vec![10, 20];
-		assert_ok!(LiquidStaking::apply_votes(Origin::root()));
+		let expected_set = vec![11, 21];
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
 		assert_eq!(expected_set, Staking::nominators(&pot_account).unwrap().targets.into_inner());
 	});
 }
 
+#[test]
+fn snapshot_voting_follows_transfers() {
+	ExtBuilder::default().build().execute_with(|| {
+		LiquidVotingMode::set(VotingMode::Snapshot);
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None));
+
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 1500));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), POOL, 21, 1000));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), POOL, 11, 500));
+		// cannot intend more than the liquid balance held
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(102), POOL, 11, 501),
+			Error::<Test>::InsufficientLiquidBalance
+		);
+
+		// nothing is reserved, so the tokens stay transferable
+		assert_eq!(
+			<Currencies as MultiReservableCurrency<_>>::reserved_balance(LIQUID_CURRENCY_ID, &101),
+			0
+		);
+		assert_ok!(Currencies::transfer(Origin::signed(101), 102, LIQUID_CURRENCY_ID, 1200));
+
+		// 101 only holds 800 now, so 31 falls behind 21.
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(vec![21, 31], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(LiquidStaking::vote_intents((POOL, 101, 31)), 0);
+		assert_eq!(LiquidStaking::vote_intents((POOL, 102, 21)), 0);
+	});
+}
+
+#[test]
+fn validator_set_is_managed_by_admin() {
+	ExtBuilder::default().approved_validators(vec![]).build().execute_with(|| {
+		assert_err!(LiquidStaking::add_validator(Origin::signed(1), 11), BadOrigin);
+		// 41 is idle and 100 is a nominator
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 41),
+			Error::<Test>::NotAValidator
+		);
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 100),
+			Error::<Test>::NotAValidator
+		);
+
+		assert_ok!(LiquidStaking::add_validator(Origin::root(), 11));
+		assert_eq!(LiquidStaking::approved_validators(11), Some(()));
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 11),
+			Error::<Test>::ValidatorAlreadyApproved
+		);
+
+		assert_ok!(LiquidStaking::remove_validator(Origin::root(), 11));
+		assert_eq!(LiquidStaking::approved_validators(11), None);
+		assert_err!(
+			LiquidStaking::remove_validator(Origin::root(), 11),
+			Error::<Test>::ValidatorNotApproved
+		);
+	});
+}
+
+#[test]
+fn approved_validator_set_is_bounded() {
+	ExtBuilder::default().build().execute_with(|| {
+		MaxValidatorCount::set(3);
+		assert_ok!(Staking::validate(Origin::signed(40), Default::default()));
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 41),
+			Error::<Test>::TooManyValidators
+		);
+
+		MaxValidatorCount::set(5);
+		assert_ok!(LiquidStaking::add_validator(Origin::root(), 41));
+	});
+}
+
+#[test]
+fn only_approved_validators_can_be_voted_for() {
+	ExtBuilder::default().approved_validators(vec![11, 21]).build().execute_with(|| {
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 31, 100),
+			Error::<Test>::ValidatorNotApproved
+		);
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 10, 100),
+			Error::<Test>::ValidatorNotApproved
+		);
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 100));
+	});
+}
+
+#[test]
+fn blocked_validators_cannot_be_voted_for() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+
+		assert_ok!(LiquidStaking::block_validator(Origin::root(), 31));
+		assert_eq!(LiquidStaking::approved_validators(31), None);
+		assert_err!(
+			LiquidStaking::block_validator(Origin::root(), 31),
+			Error::<Test>::ValidatorBlocked
+		);
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 31, 100),
+			Error::<Test>::ValidatorBlocked
+		);
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 31),
+			Error::<Test>::ValidatorBlocked
+		);
+
+		assert_ok!(LiquidStaking::unblock_validator(Origin::root(), 31));
+		assert_err!(
+			LiquidStaking::unblock_validator(Origin::root(), 31),
+			Error::<Test>::ValidatorNotBlocked
+		);
+		// unblocking does not approve the validator again
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 31, 100),
+			Error::<Test>::ValidatorNotApproved
+		);
+		assert_ok!(LiquidStaking::add_validator(Origin::root(), 31));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 100));
+	});
+}
+
+#[test]
+fn chilled_validators_are_removed_when_votes_are_applied() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 100));
+
+		// 31 stops validating after receiving the most votes
+		assert_ok!(Staking::chill(Origin::signed(30)));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		assert_eq!(vec![21, 11], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(LiquidStaking::approved_validators(31), None);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::ValidatorAutoRemoved {
+			validator: 31,
+			reason: RemovalReason::Chilled,
+		}));
+	});
+}
+
+#[test]
+fn chilled_nominee_is_dropped_right_away() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(vec![31, 21], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+
+		assert_ok!(Staking::chill(Origin::signed(30)));
+		run_to_block(System::block_number() + 1);
+
+		assert_eq!(vec![21], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(LiquidStaking::approved_validators(31), None);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::NominationRemoved {
+			pool_id: POOL,
+			validator: 31,
+			reason: RemovalReason::Chilled,
+		}));
+	});
+}
+
+#[test]
+fn slashed_nominee_is_dropped_right_away() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		slash_validator(21, Perbill::from_percent(10));
+
+		assert_eq!(vec![31], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(LiquidStaking::approved_validators(21), None);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::ValidatorAutoRemoved {
+			validator: 21,
+			reason: RemovalReason::Slashed,
+		}));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::NominationRemoved {
+			pool_id: POOL,
+			validator: 21,
+			reason: RemovalReason::Slashed,
+		}));
+	});
+}
+
+#[test]
+fn pot_stops_nominating_when_no_nominee_is_left() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		slash_validator(11, Perbill::from_percent(10));
+		slash_validator(21, Perbill::from_percent(10));
+
+		assert!(Staking::nominators(&pot_account).is_none());
+	});
+}
+
+/// Era points, commission and self stake differ between 11, 21 and 31 in the completed era 1.
+fn setup_scored_validators() {
+	start_active_era(1);
+	Staking::reward_by_ids(vec![(11, 100), (21, 50)]);
+	assert_ok!(Staking::validate(
+		Origin::signed(20),
+		ValidatorPrefs { commission: Perbill::from_percent(50), blocked: false }
+	));
+	start_active_era(2);
+	assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+}
+
+#[test]
+fn validator_scores_are_updated_when_votes_are_applied() {
+	ExtBuilder::default().build().execute_with(|| {
+		setup_scored_validators();
+		assert_eq!(LiquidStaking::validator_score(11), None);
+
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 100));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		assert_eq!(LiquidStaking::validator_score(11).unwrap().total, Perbill::one());
+		assert_eq!(
+			LiquidStaking::validator_score(21).unwrap(),
+			ValidatorScore {
+				era_points: Perbill::from_percent(50),
+				commission: Perbill::from_percent(50),
+				slashing: Perbill::one(),
+				self_stake: Perbill::one(),
+				total: Perbill::from_percent(75),
+			}
+		);
+		// no points and half the self stake of the others
+		assert_eq!(LiquidStaking::validator_score(31).unwrap().total, Perbill::from_parts(625_000_000));
+
+		// removed validators lose their score
+		assert_ok!(LiquidStaking::remove_validator(Origin::root(), 31));
+		assert_eq!(LiquidStaking::validator_score(31), None);
+	});
+}
+
+#[test]
+fn slashed_validators_score_lower() {
+	ExtBuilder::default().build().execute_with(|| {
+		setup_scored_validators();
+		slash_validator(11, Perbill::from_percent(10));
+		// get 11 back in the approved set
+		assert_ok!(Staking::validate(Origin::signed(10), Default::default()));
+		assert_ok!(LiquidStaking::add_validator(Origin::root(), 11));
+
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 100));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(LiquidStaking::validator_score(11).unwrap().slashing, Perbill::zero());
+	});
+}
+
+/// Slashing spans of staking, which are crate private: (index, start, last nonzero slash, prior).
+#[frame_support::storage_alias]
+type SlashingSpans = StorageMap<
+	Staking,
+	frame_support::Twox64Concat,
+	AccountId,
+	(u32, EraIndex, EraIndex, Vec<EraIndex>),
+>;
+
+#[test]
+fn slashing_spans_without_a_slash_do_not_lower_the_score() {
+	ExtBuilder::default().build().execute_with(|| {
+		setup_scored_validators();
+		// the spans staking keeps for 21 before any nonzero slash
+		SlashingSpans::insert(21, (0, 0, 0, Vec::new()));
+		assert!(Staking::slashing_spans(21).is_some());
+
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 100));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(LiquidStaking::validator_score(21).unwrap().slashing, Perbill::one());
+	});
+}
+
+#[test]
+fn selection_blends_votes_and_scores() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		setup_scored_validators();
+
+		// votes only
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 1000));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(vec![31], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+
+		// scores only
+		assert_ok!(LiquidStaking::set_nomination_policy(
+			Origin::root(),
+			POOL,
+			nomination_policy(2, Perbill::one()),
+		));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 1000));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(vec![11, 21], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+
+		// 31: 50% * 100% + 50% * 62.5%, 11: 50% * 100%, 21: 50% * 75%
+		assert_ok!(LiquidStaking::set_nomination_policy(
+			Origin::root(),
+			POOL,
+			nomination_policy(2, Perbill::from_percent(50)),
+		));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 1000));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(vec![31, 11], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+	});
+}
+
+#[test]
+fn applying_votes_needs_a_candidate() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err!(
+			LiquidStaking::apply_votes(Origin::root(), POOL),
+			Error::<Test>::NoValidatorsToNominate
+		);
+	});
+}
+
+fn set_commission(controller: AccountId, percent: u32) {
+	assert_ok!(Staking::validate(
+		Origin::signed(controller),
+		ValidatorPrefs { commission: Perbill::from_percent(percent), blocked: false }
+	));
+}
+
+#[test]
+fn max_commission_is_set_by_admin() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err!(
+			LiquidStaking::set_max_commission(Origin::signed(1), Some(Perbill::from_percent(10))),
+			BadOrigin
+		);
+		assert_ok!(LiquidStaking::set_max_commission(Origin::root(), Some(Perbill::from_percent(10))));
+		assert_eq!(LiquidStaking::max_commission(), Some(Perbill::from_percent(10)));
+		assert_ok!(LiquidStaking::set_max_commission(Origin::root(), None));
+		assert_eq!(LiquidStaking::max_commission(), None);
+	});
+}
+
+#[test]
+fn validators_above_max_commission_cannot_be_voted_for() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::set_max_commission(Origin::root(), Some(Perbill::from_percent(10))));
+
+		set_commission(20, 11);
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 21, 100),
+			Error::<Test>::CommissionTooHigh
+		);
+		set_commission(20, 10);
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 100));
+	});
+}
+
+#[test]
+fn validators_above_max_commission_are_not_selected() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 100));
+
+		// 31 raises its commission after being voted for
+		set_commission(30, 50);
+		assert_ok!(LiquidStaking::set_max_commission(Origin::root(), Some(Perbill::from_percent(10))));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		assert_eq!(vec![21, 11], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		// it stays approved and can come back with a lower commission
+		assert_eq!(LiquidStaking::approved_validators(31), Some(()));
+	});
+}
+
+#[test]
+fn nominee_raising_commission_above_cap_is_dropped() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+		assert_ok!(LiquidStaking::set_max_commission(Origin::root(), Some(Perbill::from_percent(10))));
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		set_commission(20, 100);
+		run_to_block(System::block_number() + 1);
+
+		assert_eq!(vec![31], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(LiquidStaking::approved_validators(21), Some(()));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::NominationRemoved {
+			pool_id: POOL,
+			validator: 21,
+			reason: RemovalReason::CommissionAboveCap,
+		}));
+	});
+}
+
 #[test]
 fn request_unbond_works() {
 	ExtBuilder::default().build().execute_with(|| {
 		assert_eq_uvec!(validator_controllers(), vec![20, 10]);
 
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), 200));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
 		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 2000);
-		assert_eq!(LiquidStaking::liquid_to_staking(100).unwrap(), 10);
+		assert_eq!(LiquidStaking::liquid_to_staking(POOL, 100).unwrap(), 10);
 
-		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), 100));
-		let unbond_req = LiquidStaking::unbonding_requests(&101);
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 100, None, None));
+		let unbond_req = LiquidStaking::unbonding_requests(POOL, 0);
 		assert_eq!(unbond_req.is_some(), true);
-		assert_eq!(unbond_req.unwrap(), (10, 100, 0));
+		assert_eq!(unbond_req.unwrap(), unbonding(10, 100, 0));
+	});
+}
+
+#[test]
+fn bond_and_unbond_respect_minimum_out_and_deadline() {
+	ExtBuilder::default().build().execute_with(|| {
+		System::set_block_number(5);
+
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, Some(2001), None),
+			Error::<Test>::InsufficientLiquidOut
+		);
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, Some(4)),
+			Error::<Test>::DeadlinePassed
+		);
+		assert_ok!(LiquidStaking::bond_and_mint(
+			Origin::signed(101),
+			POOL,
+			200,
+			Some(2000),
+			Some(5)
+		));
+
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 100, Some(11), None),
+			Error::<Test>::InsufficientStakingOut
+		);
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 100, None, Some(4)),
+			Error::<Test>::DeadlinePassed
+		);
+		assert_ok!(LiquidStaking::request_unbond(
+			Origin::signed(101),
+			POOL,
+			100,
+			Some(10),
+			Some(5)
+		));
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), Some(unbonding(10, 100, 0)));
 	});
 }
 
 #[test]
 fn mint_rate_is_consistent() {
 	ExtBuilder::default().build().execute_with(|| {
-		assert_eq!(LiquidStaking::current_mint_rate(), MintRate::saturating_from_rational(10, 1));
+		assert_eq!(
+			LiquidStaking::current_mint_rate(POOL),
+			MintRate::saturating_from_rational(10, 1)
+		);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_eq!(
+			LiquidStaking::current_mint_rate(POOL),
+			MintRate::saturating_from_rational(10, 1)
+		);
+
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 100, None, None));
+		assert_eq!(
+			LiquidStaking::current_mint_rate(POOL),
+			MintRate::saturating_from_rational(10, 1)
+		);
+	});
+}
+
+/// Deterministic pseudo random numbers for the conversion properties.
+struct Lcg(u64);
+
+impl Lcg {
+	fn next(&mut self) -> u128 {
+		self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+		(self.0 >> 16) as u128
+	}
+
+	/// A non zero number of up to 96 bits.
+	fn amount(&mut self) -> u128 {
+		let bits = self.next() % 96 + 1;
+		(((self.next() << 48) | self.next()) >> (96 - bits)).max(1)
+	}
+}
+
+#[test]
+fn conversion_rounds_as_asked() {
+	let mut rng = Lcg(42);
+	for _ in 0..10_000 {
+		let (amount, numerator, denominator) = (rng.amount(), rng.amount(), rng.amount());
+		let down = conversion::mul_div(amount, numerator, denominator, Rounding::Down).unwrap();
+		let up = conversion::mul_div(amount, numerator, denominator, Rounding::Up).unwrap();
+
+		assert!(up - down <= 1);
+		if let Some(product) = amount.checked_mul(numerator) {
+			assert_eq!(down, product / denominator);
+			assert_eq!(up == down, product % denominator == 0);
+		}
+	}
+}
+
+#[test]
+fn conversion_uses_full_width() {
+	assert_eq!(
+		conversion::mul_div(u128::MAX, u128::MAX, u128::MAX, Rounding::Down),
+		Ok(u128::MAX)
+	);
+	assert_eq!(
+		conversion::mul_div(u128::MAX, 3, 2, Rounding::Down),
+		Err(ArithmeticError::Overflow)
+	);
+	assert_eq!(
+		conversion::mul_div(1u128, 1, 0, Rounding::Up),
+		Err(ArithmeticError::DivisionByZero)
+	);
+	// the result is bounded by the balance type
+	assert_eq!(
+		conversion::mul_div(u32::MAX, 2, 1, Rounding::Down),
+		Err(ArithmeticError::Overflow)
+	);
+}
+
+#[test]
+fn conversion_round_trips_do_not_create_value() {
+	let mut rng = Lcg(7);
+	for _ in 0..10_000 {
+		let (total_liquid, total_staking, amount) = (rng.amount(), rng.amount(), rng.amount());
+		let convert = |amount, numerator, denominator, rounding| {
+			conversion::mul_div(amount, numerator, denominator, rounding).unwrap_or(0)
+		};
+
+		// mint then redeem
+		let liquid = convert(amount, total_liquid, total_staking, Rounding::Down);
+		assert!(convert(liquid, total_staking, total_liquid, Rounding::Down) <= amount);
+
+		// redeem then mint
+		let staking = convert(amount, total_staking, total_liquid, Rounding::Down);
+		assert!(convert(staking, total_liquid, total_staking, Rounding::Down) <= amount);
+
+		// shares charged for a rebasing amount are worth at least that amount
+		if let Ok(shares) = conversion::mul_div(amount, total_liquid, total_staking, Rounding::Up) {
+			assert!(convert(shares, total_staking, total_liquid, Rounding::Up) >= amount);
+		}
+	}
+}
+
+#[test]
+fn rounding_does_not_pay_out_more_than_bonded() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		// an uneven reward
+		reward_pot(POOL, 7);
 
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), 200));
-		assert_eq!(LiquidStaking::current_mint_rate(), MintRate::saturating_from_rational(10, 1));
+		// 13 * 2100 / 217 = 125.8
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 13, None, None));
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &102), 125);
 
-		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), 100));
-		assert_eq!(LiquidStaking::current_mint_rate(), MintRate::saturating_from_rational(10, 1));
+		// 125 * 230 / 2225 = 12.9
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(102), POOL, 125, None, None));
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), Some(unbonding(12, 125, 0)));
 	});
 }
 
 #[test]
 fn request_unbond_before_unbond_duration_not_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), 200));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
 
-		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), 100));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 100, None, None));
 
 		assert_err!(
-			LiquidStaking::withdraw_unbonded(Origin::signed(101)),
+			LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0),
 			Error::<Test>::UnbondingWaitNotComplete
 		);
 	});
@@ -156,45 +806,1429 @@ fn request_unbond_before_unbond_duration_not_works() {
 #[test]
 fn request_unbond_after_unbond_duration_works() {
 	ExtBuilder::default().build().execute_with(|| {
-		assert_eq!(LiquidStaking::total_liquid_issuance(), 0);
+		// the seed of the pool
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 100);
 
 		start_active_era(2);
-		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), 200));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
 		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 800);
 		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 2000);
 		// liquid tokens are minted
-		assert_eq!(LiquidStaking::total_liquid_issuance(), 2000);
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 2100);
 
 		start_active_era(3);
 		// liquid currency used to get back staking currency
 		let burn_amount = 100;
-		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), burn_amount));
+		assert_ok!(LiquidStaking::request_unbond(
+			Origin::signed(101),
+			POOL,
+			burn_amount,
+			None,
+			None
+		));
 
 		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 800);
 		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 1900);
 
 		// unbond request at era 3, should unlock at era 6
-		let unbond_req = LiquidStaking::unbonding_requests(&101);
-		assert_eq!(unbond_req.unwrap(), (10, 100, 3));
+		let unbond_req = LiquidStaking::unbonding_requests(POOL, 0);
+		assert_eq!(unbond_req.unwrap(), unbonding(10, 100, 3));
 		assert_err!(
-			LiquidStaking::withdraw_unbonded(Origin::signed(101)),
+			LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0),
 			Error::<Test>::UnbondingWaitNotComplete
 		);
 
 		start_active_era(5);
 		// locked at era 5
 		assert_err!(
-			LiquidStaking::withdraw_unbonded(Origin::signed(101)),
+			LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0),
 			Error::<Test>::UnbondingWaitNotComplete
 		);
 
 		start_active_era(6);
 		// user free to withdraw unbond at era 6
-		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101)));
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
 		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 810);
 		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 1900);
 		// liquid token is burnt
-		assert_eq!(LiquidStaking::total_liquid_issuance(), 2000 - burn_amount);
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 2100 - burn_amount);
+	});
+}
+
+#[test]
+fn unbonding_can_be_cancelled_before_it_completes() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+		assert_err!(
+			LiquidStaking::cancel_unbond(Origin::signed(101), POOL, 0),
+			Error::<Test>::UnbondingRequestNotExist
+		);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), Some(unbonding(100, 1000, 1)));
+		// rewards double the pot while unbonding
+		reward_pot(POOL, 210);
+
+		assert_ok!(LiquidStaking::cancel_unbond(Origin::signed(101), POOL, 0));
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), None);
+		assert!(Staking::ledger(pot_account).unwrap().unlocking.is_empty());
+		// the stake comes back at the rate of the rest of the pool, 100 * 1100 / 320
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 1343);
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 1443);
+		// the pot keeps its seed only
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &pot_account), 100);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::UnbondCancelled {
+			pool_id: POOL,
+			claim_id: 0,
+			who: 101,
+			staking_amount: 100,
+			liquid_amount: 343,
+			rate: MintRate::saturating_from_rational(1100, 320),
+		}));
+	});
+}
+
+#[test]
+fn pending_unbonding_is_left_out_of_the_rate() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_eq!(LiquidStaking::pending_unbonding(POOL), (100, 1000));
+		reward_pot(POOL, 210);
+
+		// the request keeps its 100, the other 1100 liquid are backed by 320
+		assert_eq!(LiquidStaking::backing(POOL), 320);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 320, None, None));
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &102), 1100);
+
+		start_active_era(4);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 900);
+		assert_eq!(LiquidStaking::pending_unbonding(POOL), (0, 0));
+		// the new holder is not diluted by the payout
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 2200);
+		assert_eq!(LiquidStaking::backing(POOL), 640);
+		assert_eq!(LiquidStaking::liquid_to_staking(POOL, 1100), Ok(320));
+	});
+}
+
+#[test]
+fn slashed_unbonding_requests_stay_covered() {
+	ExtBuilder::default().without_genesis_liquid().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 500));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		start_active_era(2);
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_eq!(LiquidStaking::pending_unbonding(POOL), (100, 1000));
+
+		// the stake being unbonded is slashed along with the active stake
+		slash_validator(11, Perbill::from_percent(50));
+		let ledger = Staking::ledger(&pot_account).unwrap();
+		let unlocking = ledger.total - ledger.active;
+		assert!(unlocking < 100);
+		let (pending, pending_liquid) = LiquidStaking::pending_unbonding(POOL);
+		assert!(pending <= unlocking);
+		assert_eq!(pending_liquid, 1000);
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0).unwrap().staking_amount, pending);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::UnbondingRequestsSlashed {
+			pool_id: POOL,
+			pending: 100,
+			remaining: pending,
+		}));
+		assert_ok!(LiquidStaking::sanity_check());
+
+		start_active_era(5);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 800 + pending);
+		assert_eq!(LiquidStaking::pending_unbonding(POOL), (0, 0));
+	});
+}
+
+#[test]
+fn completed_unbonding_can_not_be_cancelled() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+
+		start_active_era(4);
+		assert_err!(
+			LiquidStaking::cancel_unbond(Origin::signed(101), POOL, 0),
+			Error::<Test>::UnbondingAlreadyComplete
+		);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+	});
+}
+
+#[test]
+fn anyone_can_pay_out_a_completed_unbonding() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_eq!(LiquidStaking::unbonding_queue().into_inner(), vec![(POOL, 0)]);
+
+		start_active_era(4);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(102), POOL, 0));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 900);
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), None);
+		assert!(LiquidStaking::unbonding_queue().is_empty());
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::Withdraw {
+			pool_id: POOL,
+			claim_id: 0,
+			who: 101,
+			staking_amount: 100,
+			liquid_amount: 1000,
+			era: 4,
+		}));
+
+		// paid only once
+		assert_err!(
+			LiquidStaking::withdraw_unbonded(Origin::signed(102), POOL, 0),
+			Error::<Test>::UnbondingRequestNotExist
+		);
+	});
+}
+
+#[test]
+fn completed_unbondings_are_paid_out_in_idle_blocks() {
+	ExtBuilder::default().build().execute_with(|| {
+		AutoWithdraw::set(true);
+		start_active_era(1);
+		for who in [101, 102] {
+			assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(who), POOL, 200, None, None));
+			assert_ok!(LiquidStaking::request_unbond(Origin::signed(who), POOL, 1000, None, None));
+		}
+		start_active_era(2);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(103), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(103), POOL, 1000, None, None));
+
+		start_active_era(4);
+		let block = System::block_number();
+		// not enough weight left for a request
+		LiquidStaking::on_idle(block, LiquidStaking::withdraw_weight());
+		assert_eq!(LiquidStaking::unbonding_queue().len(), 3);
+
+		// enough for one, in the order of the requests
+		LiquidStaking::on_idle(block, 2 * LiquidStaking::withdraw_weight());
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 900);
+		assert_eq!(LiquidStaking::unbonding_queue().into_inner(), vec![(POOL, 1), (POOL, 2)]);
+
+		// the request of 103 completes an era later
+		LiquidStaking::on_idle(block, Weight::MAX);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &102), 900);
+		assert_eq!(LiquidStaking::unbonding_queue().into_inner(), vec![(POOL, 2)]);
+
+		start_active_era(5);
+		LiquidStaking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &103), 900);
+		assert!(LiquidStaking::unbonding_queue().is_empty());
+		AutoWithdraw::set(false);
+	});
+}
+
+#[test]
+fn failed_payouts_stay_queued() {
+	use orml_traits::MultiCurrency;
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		AutoWithdraw::set(true);
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		// the liquid currency of the request can not be burnt
+		assert_ok!(<Currencies as MultiCurrency<_>>::withdraw(
+			LIQUID_CURRENCY_ID,
+			&pot_account,
+			1000
+		));
+
+		start_active_era(4);
+		LiquidStaking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 800);
+		assert_eq!(LiquidStaking::unbonding_queue().into_inner(), vec![(POOL, 0)]);
+
+		assert_ok!(<Currencies as MultiCurrency<_>>::deposit(
+			LIQUID_CURRENCY_ID,
+			&pot_account,
+			1000
+		));
+		LiquidStaking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 900);
+		assert!(LiquidStaking::unbonding_queue().is_empty());
+		AutoWithdraw::set(false);
+	});
+}
+
+#[test]
+fn pending_unbonding_requests_are_bounded_per_account() {
+	ExtBuilder::default().build().execute_with(|| {
+		MaxUnbondingRequests::set(1);
+		for who in [101, 102, 103] {
+			assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(who), POOL, 200, None, None));
+		}
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None),
+			Error::<Test>::TooManyUnbondingRequests
+		);
+		// other accounts are not held up
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(102), POOL, 1000, None, None));
+		assert_eq!(LiquidStaking::unbonding_request_count(101), 1);
+
+		// claims count for their owner
+		assert_err!(
+			LiquidStaking::transfer_claim(Origin::signed(101), POOL, 0, 102),
+			Error::<Test>::TooManyUnbondingRequests
+		);
+		assert_ok!(LiquidStaking::transfer_claim(Origin::signed(101), POOL, 0, 103));
+		assert_eq!(LiquidStaking::unbonding_request_count(101), 0);
+		assert_eq!(LiquidStaking::unbonding_request_count(103), 1);
+
+		// cancelling frees the slot
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+		assert_ok!(LiquidStaking::cancel_unbond(Origin::signed(101), POOL, 2));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+		MaxUnbondingRequests::set(16);
+	});
+}
+
+#[test]
+fn requests_beyond_the_withdrawal_queue_are_accepted() {
+	ExtBuilder::default().build().execute_with(|| {
+		MaxQueuedUnbondings::set(1);
+		start_active_era(1);
+		for who in [101, 102] {
+			assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(who), POOL, 200, None, None));
+			assert_ok!(LiquidStaking::request_unbond(Origin::signed(who), POOL, 1000, None, None));
+		}
+		assert_eq!(LiquidStaking::unbonding_queue().into_inner(), vec![(POOL, 0)]);
+
+		// the request left out of the queue is withdrawn by hand
+		AutoWithdraw::set(true);
+		start_active_era(4);
+		LiquidStaking::on_idle(System::block_number(), Weight::MAX);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 900);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &102), 800);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(102), POOL, 1));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &102), 900);
+		AutoWithdraw::set(false);
+		MaxQueuedUnbondings::set(16);
+	});
+}
+
+#[test]
+fn unbonding_claims_can_be_transferred() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		// every request is a claim of its own
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+		assert_eq!(<LiquidStaking as Inspect<AccountId>>::owner(&POOL, &0), Some(101));
+		assert_eq!(<LiquidStaking as Inspect<AccountId>>::owner(&POOL, &1), Some(101));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::RequestUnbond {
+			pool_id: POOL,
+			claim_id: 1,
+			who: 101,
+			liquid_amount: 500,
+			staking_amount: 50,
+			rate: MintRate::saturating_from_integer(10),
+			era: 1,
+		}));
+
+		assert_err!(
+			LiquidStaking::transfer_claim(Origin::signed(102), POOL, 0, 103),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_ok!(LiquidStaking::transfer_claim(Origin::signed(101), POOL, 0, 102));
+		assert_eq!(LiquidStaking::claim_owner(POOL, 0), Some(102));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::ClaimTransferred {
+			pool_id: POOL,
+			claim_id: 0,
+			from: 101,
+			to: 102,
+		}));
+		// the claim can only be cancelled by its owner
+		assert_err!(
+			LiquidStaking::cancel_unbond(Origin::signed(101), POOL, 0),
+			Error::<Test>::NotClaimOwner
+		);
+		assert_ok!(<LiquidStaking as Transfer<AccountId>>::transfer(&POOL, &1, &103));
+		assert_err!(
+			<LiquidStaking as Transfer<AccountId>>::transfer(&POOL, &2, &103),
+			Error::<Test>::UnbondingRequestNotExist
+		);
+
+		// completed requests pay the owners of the claims
+		start_active_era(4);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 1));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 800);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &102), 1100);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &103), 1050);
+		assert_eq!(<LiquidStaking as Inspect<AccountId>>::owner(&POOL, &0), None);
+	});
+}
+
+#[test]
+fn operations_can_be_paused() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+
+		assert_err!(
+			LiquidStaking::set_pause(Origin::signed(101), POOL, PauseFlags::all()),
+			BadOrigin
+		);
+		assert_ok!(LiquidStaking::set_pause(
+			Origin::root(),
+			POOL,
+			PauseFlags::BOND | PauseFlags::VOTE
+		));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::PauseSet {
+			pool_id: POOL,
+			flags: PauseFlags::BOND | PauseFlags::VOTE,
+		}));
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None),
+			Error::<Test>::Paused
+		);
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 11, 100),
+			Error::<Test>::Paused
+		);
+		// other operations go on
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+
+		assert_ok!(LiquidStaking::set_pause(Origin::root(), POOL, PauseFlags::WITHDRAW));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None));
+		start_active_era(4);
+		assert_err!(
+			LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0),
+			Error::<Test>::Paused
+		);
+
+		assert_ok!(LiquidStaking::set_pause(Origin::root(), POOL, PauseFlags::empty()));
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+	});
+}
+
+#[test]
+fn holders_redeem_pro_rata_in_emergency_exit() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 100, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(102), POOL, 500, None, None));
+		assert_err!(
+			LiquidStaking::emergency_redeem(Origin::signed(101), POOL, 1000),
+			Error::<Test>::EmergencyExitNotEnabled
+		);
+
+		assert_ok!(Staking::nominate(Origin::signed(pot_account), vec![11]));
+		assert_ok!(LiquidStaking::enable_emergency_exit(Origin::root(), POOL));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::EmergencyExitEnabled {
+			pool_id: POOL,
+		}));
+		assert_eq!(Staking::nominators(pot_account), None);
+		assert_eq!(Staking::ledger(pot_account).unwrap().active, 0);
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(103), POOL, 200, None, None),
+			Error::<Test>::EmergencyExitEnabled
+		);
+		assert_err!(
+			LiquidStaking::enable_emergency_exit(Origin::root(), POOL),
+			Error::<Test>::EmergencyExitEnabled
+		);
+		// the stake is still unbonding
+		assert_err!(
+			LiquidStaking::emergency_redeem(Origin::signed(101), POOL, 1000),
+			Error::<Test>::EmergencyFundsLocked
+		);
+
+		start_active_era(4);
+		// even a pause does not keep holders in the pool
+		assert_ok!(LiquidStaking::set_pause(Origin::root(), POOL, PauseFlags::all()));
+		assert_ok!(LiquidStaking::emergency_redeem(Origin::signed(101), POOL, 2000));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 1000);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::EmergencyRedeemed {
+			pool_id: POOL,
+			who: 101,
+			liquid_amount: 2000,
+			staking_amount: 200,
+		}));
+
+		// pending unbonding requests are paid as usual
+		assert_ok!(LiquidStaking::set_pause(Origin::root(), POOL, PauseFlags::empty()));
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(102), POOL, 0));
+		assert_ok!(LiquidStaking::emergency_redeem(Origin::signed(102), POOL, 500));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &102), 1000);
+		// only the seed is left
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 100);
+		assert_eq!(LiquidStaking::backing(POOL), 10);
+	});
+}
+
+#[test]
+fn emergency_redemptions_leave_pending_requests_their_stake() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 100, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(102), POOL, 500, None, None));
+		start_active_era(2);
+		assert_ok!(LiquidStaking::enable_emergency_exit(Origin::root(), POOL));
+
+		// only the stake of the request is unlocked, and it is owed to the request
+		start_active_era(4);
+		assert_err!(
+			LiquidStaking::emergency_redeem(Origin::signed(101), POOL, 500),
+			Error::<Test>::EmergencyFundsLocked
+		);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(102), POOL, 0));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &102), 950);
+
+		start_active_era(5);
+		assert_ok!(LiquidStaking::emergency_redeem(Origin::signed(101), POOL, 2000));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 1000);
+	});
+}
+
+#[test]
+fn bonds_are_kept_within_deposit_caps() {
+	ExtBuilder::default().build().execute_with(|| {
+		let caps = DepositCaps {
+			max_stake: Some(400),
+			max_era_deposits: Some(250),
+			max_account_liquid: Some(1500),
+		};
+		assert_err!(LiquidStaking::set_deposit_caps(Origin::signed(101), POOL, caps), BadOrigin);
+		assert_ok!(LiquidStaking::set_deposit_caps(Origin::root(), POOL, caps));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::DepositCapsSet {
+			pool_id: POOL,
+			caps,
+		}));
+
+		start_active_era(1);
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None),
+			Error::<Test>::AccountLiquidCapExceeded
+		);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 150, None, None));
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 150, None, None),
+			Error::<Test>::EraDepositCapExceeded
+		);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 100, None, None));
+		assert_eq!(LiquidStaking::era_deposits(POOL), (1, 250));
+
+		// deposits start over in the next era, the stake is capped as a whole
+		start_active_era(2);
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(103), POOL, 150, None, None),
+			Error::<Test>::StakeCapExceeded
+		);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(103), POOL, 140, None, None));
+		assert_eq!(LiquidStaking::backing(POOL), 400);
+	});
+}
+
+#[test]
+fn params_override_the_constants() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err!(
+			LiquidStaking::set_params(
+				Origin::signed(101),
+				ConfigOp::Set(50),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Noop
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			LiquidStaking::set_params(
+				Origin::root(),
+				ConfigOp::Noop,
+				ConfigOp::Noop,
+				ConfigOp::Set(MintRate::from_inner(0)),
+				ConfigOp::Noop
+			),
+			Error::<Test>::ZeroMintRate
+		);
+		assert_ok!(LiquidStaking::set_params(
+			Origin::root(),
+			ConfigOp::Set(50),
+			ConfigOp::Noop,
+			ConfigOp::Set(MintRate::saturating_from_integer(5)),
+			ConfigOp::Set(3)
+		));
+		let params = Params {
+			bond_threshold: Some(50),
+			unbond_threshold: None,
+			default_mint_rate: Some(MintRate::saturating_from_integer(5)),
+			max_validator_count: Some(3),
+		};
+		assert_eq!(LiquidStaking::params(), params);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::ParamsSet { params }));
+
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 40, None, None),
+			Error::<Test>::BelowBondThreshold
+		);
+		assert_ok!(Staking::validate(Origin::signed(40), Default::default()));
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 41),
+			Error::<Test>::TooManyValidators
+		);
+		// a pool without its own mint rate is seeded at the default one
+		let pool_id = LiquidStaking::next_pool_id();
+		fund_pot(pool_id);
+		assert_ok!(LiquidStaking::create_pool(
+			Origin::root(),
+			OTHER_LIQUID_CURRENCY_ID,
+			None,
+			None,
+			None,
+			nomination_policy(1, Perbill::zero()),
+			AccountingMode::ExchangeRate,
+		));
+		assert_eq!(LiquidStaking::total_liquid_issuance(pool_id), 50);
+
+		// removed parameters fall back to the constants again
+		assert_ok!(LiquidStaking::set_params(
+			Origin::root(),
+			ConfigOp::Remove,
+			ConfigOp::Noop,
+			ConfigOp::Noop,
+			ConfigOp::Remove
+		));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 40, None, None));
+		assert_ok!(LiquidStaking::add_validator(Origin::root(), 41));
+	});
+}
+
+#[test]
+fn zero_amounts_are_rejected() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 0, None, None),
+			Error::<Test>::ZeroAmount
+		);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 0, None, None),
+			Error::<Test>::ZeroAmount
+		);
+		// nothing would be unbonded for it
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 9, None, None),
+			Error::<Test>::ZeroAmount
+		);
+		assert_err!(
+			LiquidStaking::vote(Origin::signed(101), POOL, 11, 0),
+			Error::<Test>::ZeroAmount
+		);
+	});
+}
+
+#[test]
+fn unbonding_below_threshold_is_rejected() {
+	ExtBuilder::default().build().execute_with(|| {
+		UnbondThreshold::set(500);
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 499, None, None),
+			Error::<Test>::BelowUnbondThreshold
+		);
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+		UnbondThreshold::set(0);
+	});
+}
+
+#[test]
+fn accounts_are_not_left_below_existential_deposit() {
+	ExtBuilder::default().build().execute_with(|| {
+		// the existential deposit of the staking currency is 2
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 999, None, None),
+			Error::<Test>::BelowExistentialDeposit
+		);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 998, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 1000, None, None));
+	});
+}
+
+#[test]
+fn currency_errors_are_not_ignored() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		// 102 holds no liquid currency
+		assert_err!(
+			LiquidStaking::request_unbond(Origin::signed(102), POOL, 1000, None, None),
+			orml_tokens::Error::<Test>::BalanceTooLow
+		);
+		assert!(Staking::ledger(LiquidStaking::pot_account(POOL)).unwrap().unlocking.is_empty());
+	});
+}
+
+#[test]
+fn unbonding_without_a_current_era_is_rejected() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		pallet_staking::CurrentEra::<Test>::kill();
+		assert_noop!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None),
+			Error::<Test>::CurrentEraNotSet
+		);
+	});
+}
+
+#[test]
+fn remote_pots_must_be_bonded_once() {
+	ExtBuilder::default().without_genesis_liquid().remote_staking().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		assert_noop!(MockStakingBackend::bond(&pot_account, 10), Error::<Test>::PotAlreadyBonded);
+		RemoteLedgers::<Test>::remove(pot_account);
+		assert_noop!(
+			MockStakingBackend::bond_extra(&pot_account, 10),
+			Error::<Test>::PotNotBonded
+		);
+	});
+}
+
+#[test]
+fn remote_pots_cannot_unbond_more_than_their_active_stake() {
+	ExtBuilder::default().without_genesis_liquid().remote_staking().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+		report_remote_staking(POOL);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		RemoteLedgers::<Test>::mutate(pot_account, |ledger| ledger.as_mut().unwrap().active = 100);
+		assert_noop!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 2000, None, None),
+			Error::<Test>::InsufficientPotStake
+		);
+	});
+}
+
+#[test]
+fn remote_pots_cannot_unbond_past_the_chunk_limit() {
+	ExtBuilder::default().without_genesis_liquid().remote_staking().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		start_active_era(1);
+		report_remote_staking(POOL);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		let chunks = (100..132).map(|era| (era, 0)).collect::<Vec<_>>();
+		RemoteLedgers::<Test>::mutate(pot_account, |ledger| {
+			ledger.as_mut().unwrap().unlocking = chunks.try_into().unwrap();
+		});
+		assert_noop!(
+			LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None),
+			Error::<Test>::TooManyUnlockingChunks
+		);
+	});
+}
+
+#[test]
+fn repeated_reserve_votes_add_up() {
+	ExtBuilder::default().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 200));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 100));
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 11), 500);
+		assert_eq!(LiquidStaking::voters(POOL, 101), 600);
+
+		// every reserve is released
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(
+			<Currencies as MultiReservableCurrency<_>>::reserved_balance(LIQUID_CURRENCY_ID, &101),
+			0
+		);
+	});
+}
+
+#[test]
+fn version_0_storage_migrates_into_pool_0() {
+	use frame_support::{storage::storage_prefix, traits::ConstU32, StorageHasher, Twox64Concat};
+	type MigrateToV1 = migrations::v1::MigrateToV1<Test, ConstU32<LIQUID_CURRENCY_ID>>;
+
+	ExtBuilder::default().without_genesis_liquid().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 11, 300));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+
+		// version 0 had a single pot and kept its records by account
+		Pools::<Test>::remove(POOL);
+		PoolByCurrency::<Test>::remove(LIQUID_CURRENCY_ID);
+		NextPoolId::<Test>::kill();
+		TotalLiquidIssuance::<Test>::remove(POOL);
+		UnbondingRequests::<Test>::remove(POOL, 0);
+		ClaimOwner::<Test>::remove(POOL, 0);
+		UnbondingQueue::<Test>::kill();
+		NextClaimId::<Test>::kill();
+		PendingUnbonding::<Test>::remove(POOL);
+		LiquidVoteCount::<Test>::remove(POOL, 11);
+		Voters::<Test>::remove(POOL, 101);
+		let v0_key = |item: &[u8], who: AccountId| {
+			[&storage_prefix(b"LiquidStaking", item)[..], &Twox64Concat::hash(&who.encode())]
+				.concat()
+		};
+		sp_io::storage::set(
+			&storage_prefix(b"LiquidStaking", b"TotalLiquidIssuance"),
+			&2100u128.encode(),
+		);
+		sp_io::storage::set(
+			&v0_key(b"UnbondingRequests", 101),
+			&(100u128, 1000u128, 1 as EraIndex).encode(),
+		);
+		sp_io::storage::set(&v0_key(b"LiquidVoteCount", 11), &300u128.encode());
+		sp_io::storage::set(&v0_key(b"Voters", 101), &300u128.encode());
+		StorageVersion::new(0).put::<LiquidStaking>();
+
+		MigrateToV1::on_runtime_upgrade();
+		assert_eq!(LiquidStaking::on_chain_storage_version(), 1);
+		assert_eq!(LiquidStaking::pools(POOL).unwrap().liquid_currency_id, LIQUID_CURRENCY_ID);
+		assert_eq!(LiquidStaking::pool_of_currency(LIQUID_CURRENCY_ID), Some(POOL));
+		assert_eq!(LiquidStaking::next_pool_id(), 1);
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 2100);
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), Some(unbonding(100, 1000, 1)));
+		assert_eq!(LiquidStaking::claim_owner(POOL, 0), Some(101));
+		assert_eq!(LiquidStaking::unbonding_queue().into_inner(), vec![(POOL, 0)]);
+		assert_eq!(LiquidStaking::next_claim_id(), 1);
+		assert_eq!(LiquidStaking::pending_unbonding(POOL), (100, 1000));
+		assert_eq!(LiquidStaking::unclaimed(POOL), 0);
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 11), 300);
+		assert_eq!(LiquidStaking::voters(POOL, 101), 300);
+		assert_ok!(LiquidStaking::sanity_check());
+
+		// running it again changes nothing
+		MigrateToV1::on_runtime_upgrade();
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0), Some(unbonding(100, 1000, 1)));
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 2100);
+
+		// the migrated request is paid as usual
+		start_active_era(4);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 900);
+	});
+}
+
+#[test]
+fn pools_stay_solvent() {
+	ExtBuilder::default().without_genesis_liquid().build().execute_with(|| {
+		assert_ok!(LiquidStaking::sanity_check());
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), POOL, 11, 300));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 500, None, None));
+		assert_ok!(LiquidStaking::sanity_check());
+
+		reward_pot(POOL, 41);
+		assert_ok!(LiquidStaking::cancel_unbond(Origin::signed(101), POOL, 1));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_ok!(LiquidStaking::sanity_check());
+
+		start_active_era(4);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+		assert_ok!(LiquidStaking::sanity_check());
+	});
+}
+
+#[test]
+fn sanity_check_detects_broken_accounting() {
+	ExtBuilder::default().without_genesis_liquid().build().execute_with(|| {
+		start_active_era(1);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_ok!(LiquidStaking::sanity_check());
+
+		// liquid currency minted behind the pool's back
+		assert_ok!(Currencies::deposit(LIQUID_CURRENCY_ID, &102, 10));
+		assert_err!(
+			LiquidStaking::sanity_check(),
+			"liquid issuance of a pool does not match the currency"
+		);
+		assert_ok!(Currencies::withdraw(LIQUID_CURRENCY_ID, &102, 10));
+
+		// the pot owes more than it unbonded
+		UnbondingRequests::<Test>::mutate(POOL, 0, |request| {
+			request.as_mut().unwrap().staking_amount = 101
+		});
+		assert_err!(LiquidStaking::sanity_check(), "pending unbonding does not match the requests");
+		PendingUnbonding::<Test>::insert(POOL, (101, 1000));
+		assert_err!(LiquidStaking::sanity_check(), "unbonding requests are not covered by the pot");
+	});
+}
+
+#[test]
+fn rate_drops_are_reported_and_pause_bonding() {
+	let (pool, pool_state) = TestTransactionPoolExt::new();
+	let mut ext = ExtBuilder::default().build();
+	ext.register_extension(TransactionPoolExt::new(pool));
+	ext.execute_with(|| {
+		let run_offchain_worker =
+			|| <LiquidStaking as Hooks<u64>>::offchain_worker(System::block_number());
+		let report = crate::Call::report_rate_drop { pool_id: POOL };
+		let validate = |call| LiquidStaking::validate_unsigned(TransactionSource::External, call);
+		start_active_era(1);
+
+		// an accounting bug mints liquid currency out of thin air, within the threshold first
+		TotalLiquidIssuance::<Test>::mutate(POOL, |total| *total += 10);
+		run_offchain_worker();
+		assert!(pool_state.read().transactions.is_empty());
+		assert_err!(
+			LiquidStaking::report_rate_drop(Origin::none(), POOL),
+			Error::<Test>::NoRateDrop
+		);
+
+		// a liquid unit is backed by 10 / 12 of the staking currency it was at the era start
+		TotalLiquidIssuance::<Test>::mutate(POOL, |total| *total += 10);
+		run_offchain_worker();
+		let tx = pool_state.write().transactions.pop().unwrap();
+		let tx = TestXt::<mock::Call, ()>::decode(&mut &*tx).unwrap();
+		assert_eq!(tx.signature, None);
+		assert_eq!(tx.call, mock::Call::LiquidStaking(report.clone()));
+		assert!(validate(&report).is_ok());
+
+		assert_err!(LiquidStaking::report_rate_drop(Origin::signed(1), POOL), BadOrigin);
+		assert_ok!(LiquidStaking::report_rate_drop(Origin::none(), POOL));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::RateDropDetected {
+			pool_id: POOL,
+			era: 1,
+			snapshot: MintRate::saturating_from_integer(10),
+			rate: MintRate::saturating_from_integer(12),
+		}));
+		assert!(LiquidStaking::paused(POOL).contains(PauseFlags::BOND));
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None),
+			Error::<Test>::Paused
+		);
+
+		// reported only once
+		assert_eq!(validate(&report), Err(InvalidTransaction::Stale.into()));
+		run_offchain_worker();
+		assert!(pool_state.read().transactions.is_empty());
+	});
+}
+
+const OTHER_LIQUID_CURRENCY_ID: CurrencyId = 3;
+
+fn create_other_pool(targets: u32, accounting_mode: AccountingMode) -> PoolId {
+	let pool_id = LiquidStaking::next_pool_id();
+	fund_pot(pool_id);
+	assert_ok!(LiquidStaking::create_pool(
+		Origin::root(),
+		OTHER_LIQUID_CURRENCY_ID,
+		Some(MintRate::saturating_from_integer(5)),
+		None,
+		None,
+		nomination_policy(targets, Perbill::zero()),
+		accounting_mode,
+	));
+	pool_id
+}
+
+#[test]
+fn pools_are_created_by_admin() {
+	ExtBuilder::default().build().execute_with(|| {
+		let policy = nomination_policy(2, Perbill::zero());
+		assert_err!(
+			LiquidStaking::create_pool(
+				Origin::signed(1),
+				OTHER_LIQUID_CURRENCY_ID,
+				None,
+				None,
+				None,
+				policy,
+				AccountingMode::ExchangeRate
+			),
+			BadOrigin
+		);
+		// each liquid currency is minted by a single pool
+		assert_err!(
+			LiquidStaking::create_pool(
+				Origin::root(),
+				LIQUID_CURRENCY_ID,
+				None,
+				None,
+				None,
+				policy,
+				AccountingMode::ExchangeRate
+			),
+			Error::<Test>::LiquidCurrencyInUse
+		);
+		assert_err!(
+			LiquidStaking::create_pool(
+				Origin::root(),
+				STAKING_CURRENCY_ID,
+				None,
+				None,
+				None,
+				policy,
+				AccountingMode::ExchangeRate
+			),
+			Error::<Test>::LiquidCurrencyInUse
+		);
+		// a pot nominates between one and `MaxNominations` validators
+		for targets in [0, 17] {
+			assert_err!(
+				LiquidStaking::create_pool(
+					Origin::root(),
+					OTHER_LIQUID_CURRENCY_ID,
+					None,
+					None,
+					None,
+					nomination_policy(targets, Perbill::zero()),
+					AccountingMode::ExchangeRate,
+				),
+				Error::<Test>::InvalidNominationPolicy
+			);
+		}
+		// nothing would be minted for the seed
+		assert_noop!(
+			LiquidStaking::create_pool(
+				Origin::root(),
+				OTHER_LIQUID_CURRENCY_ID,
+				Some(MintRate::from_inner(0)),
+				None,
+				None,
+				policy,
+				AccountingMode::ExchangeRate
+			),
+			Error::<Test>::ZeroMintRate
+		);
+
+		let pool_id = create_other_pool(1, AccountingMode::ExchangeRate);
+		assert_eq!(pool_id, 1);
+		assert_eq!(LiquidStaking::pool_of_currency(OTHER_LIQUID_CURRENCY_ID), Some(pool_id));
+		assert_ne!(LiquidStaking::pot_account(pool_id), LiquidStaking::pot_account(POOL));
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::PoolCreated {
+			pool_id,
+			liquid_currency_id: OTHER_LIQUID_CURRENCY_ID,
+		}));
+
+		assert_err!(
+			LiquidStaking::set_nomination_policy(Origin::root(), 2, policy),
+			Error::<Test>::PoolNotFound
+		);
+		assert_err!(
+			LiquidStaking::bond_and_mint(Origin::signed(101), 2, 200, None, None),
+			Error::<Test>::PoolNotFound
+		);
+	});
+}
+
+#[test]
+fn pools_have_their_own_pot_and_rate() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pool_id = create_other_pool(1, AccountingMode::ExchangeRate);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), pool_id, 300, None, None));
+
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 2000);
+		assert_eq!(Currencies::free_balance(OTHER_LIQUID_CURRENCY_ID, &102), 1500);
+		// on top of the seeds
+		assert_eq!(LiquidStaking::total_liquid_issuance(POOL), 2100);
+		assert_eq!(LiquidStaking::total_liquid_issuance(pool_id), 1550);
+		assert_eq!(Staking::ledger(LiquidStaking::pot_account(POOL)).unwrap().active, 210);
+		assert_eq!(Staking::ledger(LiquidStaking::pot_account(pool_id)).unwrap().active, 310);
+	});
+}
+
+#[test]
+fn pools_nominate_by_their_own_votes_and_policy() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pool_id = create_other_pool(1, AccountingMode::ExchangeRate);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), pool_id, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 31, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 21, 200));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), pool_id, 11, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(102), pool_id, 21, 200));
+
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), pool_id));
+		// votes of the other pool are left for its own application
+		assert_eq!(LiquidStaking::liquid_vote_count(POOL, 31), 300);
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+
+		let pot_account = LiquidStaking::pot_account(POOL);
+		let other_pot_account = LiquidStaking::pot_account(pool_id);
+		assert_eq!(vec![31, 21], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(vec![11], Staking::nominators(&other_pot_account).unwrap().targets.into_inner());
+
+		// an offender is dropped by every pot nominating it
+		assert_ok!(Staking::chill(Origin::signed(10)));
+		run_to_block(System::block_number() + 1);
+		assert_eq!(vec![31, 21], Staking::nominators(&pot_account).unwrap().targets.into_inner());
+		assert_eq!(Staking::nominators(&other_pot_account), None);
+	});
+}
+
+#[test]
+fn rebasing_pool_balances_follow_the_pot() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pool_id = create_other_pool(1, AccountingMode::Rebasing);
+		start_active_era(1);
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), pool_id, 200, None, None));
+		// shares are minted at the pool rate, while balances are in staking terms
+		assert_eq!(Currencies::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 1000);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 200);
+
+		// rewards double the pot
+		reward_pot(pool_id, 210);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 400);
+
+		assert_ok!(RebasingCurrency::<Test>::transfer(OTHER_LIQUID_CURRENCY_ID, &101, &102, 100));
+		assert_eq!(Currencies::free_balance(OTHER_LIQUID_CURRENCY_ID, &102), 250);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &102), 100);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 300);
+
+		// amounts given to the pallet are in staking terms as well
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), pool_id, 100, None, None));
+		assert_eq!(LiquidStaking::unbonding_requests(pool_id, 0), Some(unbonding(100, 250, 1)));
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 200);
+
+		// currencies of exchange rate pools are passed through
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None));
+		assert_eq!(RebasingCurrency::<Test>::free_balance(LIQUID_CURRENCY_ID, &102), 2000);
+
+		// liquid currency is only issued and burned by the pools
+		for currency_id in [OTHER_LIQUID_CURRENCY_ID, LIQUID_CURRENCY_ID] {
+			assert_noop!(
+				RebasingCurrency::<Test>::deposit(currency_id, &102, 100),
+				Error::<Test>::IssuedByPool
+			);
+			assert_noop!(
+				RebasingCurrency::<Test>::withdraw(currency_id, &102, 100),
+				Error::<Test>::IssuedByPool
+			);
+		}
+	});
+}
+
+const WRAPPED_CURRENCY_ID: CurrencyId = 4;
+
+#[test]
+fn wrapped_currency_is_set_by_admin() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_err!(
+			LiquidStaking::set_wrapped_currency(Origin::signed(1), POOL, WRAPPED_CURRENCY_ID),
+			BadOrigin
+		);
+		assert_err!(
+			LiquidStaking::set_wrapped_currency(Origin::root(), POOL, LIQUID_CURRENCY_ID),
+			Error::<Test>::LiquidCurrencyInUse
+		);
+		assert_err!(
+			LiquidStaking::set_wrapped_currency(Origin::root(), 1, WRAPPED_CURRENCY_ID),
+			Error::<Test>::PoolNotFound
+		);
+		assert_err!(
+			LiquidStaking::wrap(Origin::signed(1), POOL, 100),
+			Error::<Test>::NoWrappedCurrency
+		);
+
+		assert_ok!(LiquidStaking::set_wrapped_currency(Origin::root(), POOL, WRAPPED_CURRENCY_ID));
+		assert_eq!(
+			LiquidStaking::pools(POOL).unwrap().wrapped_currency_id,
+			Some(WRAPPED_CURRENCY_ID)
+		);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::WrappedCurrencySet {
+			pool_id: POOL,
+			wrapped_currency_id: WRAPPED_CURRENCY_ID,
+		}));
+		assert_err!(
+			LiquidStaking::set_wrapped_currency(Origin::root(), POOL, 5),
+			Error::<Test>::WrappedCurrencyAlreadySet
+		);
+		// a wrapped currency can not be used as the liquid currency of another pool
+		assert_err!(
+			LiquidStaking::create_pool(
+				Origin::root(),
+				WRAPPED_CURRENCY_ID,
+				None,
+				None,
+				None,
+				nomination_policy(2, Perbill::zero()),
+				AccountingMode::ExchangeRate,
+			),
+			Error::<Test>::LiquidCurrencyInUse
+		);
+	});
+}
+
+#[test]
+fn wrapped_currency_keeps_shares_fixed() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pool_id = create_other_pool(1, AccountingMode::Rebasing);
+		let pot_account = LiquidStaking::pot_account(pool_id);
+		assert_ok!(LiquidStaking::set_wrapped_currency(
+			Origin::root(),
+			pool_id,
+			WRAPPED_CURRENCY_ID
+		));
+
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), pool_id, 200, None, None));
+		assert_ok!(LiquidStaking::wrap(Origin::signed(101), pool_id, 100));
+		assert_eq!(Currencies::free_balance(WRAPPED_CURRENCY_ID, &101), 500);
+		// next to the locked seed
+		assert_eq!(Currencies::free_balance(OTHER_LIQUID_CURRENCY_ID, &pot_account), 550);
+		assert_eq!(LiquidStaking::total_wrapped_issuance(pool_id), 500);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 100);
+
+		// rewards double the pot, the wrapped balance does not change
+		reward_pot(pool_id, 210);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 200);
+		assert_eq!(Currencies::free_balance(WRAPPED_CURRENCY_ID, &101), 500);
+		// and the wrapped currency is not reported as rebasing
+		assert_eq!(RebasingCurrency::<Test>::free_balance(WRAPPED_CURRENCY_ID, &101), 500);
+
+		assert_ok!(LiquidStaking::unwrap(Origin::signed(101), pool_id, 500));
+		assert_eq!(Currencies::free_balance(WRAPPED_CURRENCY_ID, &101), 0);
+		assert_eq!(LiquidStaking::total_wrapped_issuance(pool_id), 0);
+		assert_eq!(RebasingCurrency::<Test>::free_balance(OTHER_LIQUID_CURRENCY_ID, &101), 400);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::Unwrapped {
+			pool_id,
+			who: 101,
+			shares: 500,
+		}));
+	});
+}
+
+#[test]
+fn pools_are_seeded_at_creation() {
+	ExtBuilder::default().build().execute_with(|| {
+		let pool_id = LiquidStaking::next_pool_id();
+		let pot_account = LiquidStaking::pot_account(pool_id);
+		let create_pool = || {
+			LiquidStaking::create_pool(
+				Origin::root(),
+				OTHER_LIQUID_CURRENCY_ID,
+				None,
+				None,
+				None,
+				nomination_policy(2, Perbill::zero()),
+				AccountingMode::ExchangeRate,
+			)
+		};
+		assert_err!(create_pool(), Error::<Test>::InsufficientSeed);
+
+		fund_pot(pool_id);
+		assert_ok!(create_pool());
+		// the seed is bonded and the liquid currency minted for it stays in the pot
+		assert_eq!(Staking::ledger(pot_account).unwrap().active, 10);
+		assert_eq!(Staking::payee(pot_account), RewardDestination::Staked);
+		assert_eq!(Currencies::free_balance(OTHER_LIQUID_CURRENCY_ID, &pot_account), 100);
+		assert_eq!(LiquidStaking::total_liquid_issuance(pool_id), 100);
+	});
+}
+
+#[test]
+fn donations_to_the_pot_do_not_move_the_rate() {
+	// The first minter used to be able to send staking currency straight to the pot, inflating
+	// the rate so that later deposits round down to little or no liquid currency.
+	ExtBuilder::default().without_genesis_liquid().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		// the smallest mint there is
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 1, None, None));
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 10);
+		assert_ok!(Currencies::deposit(STAKING_CURRENCY_ID, &101, 1_000_000));
+		assert_ok!(Currencies::transfer(
+			Origin::signed(101),
+			pot_account,
+			STAKING_CURRENCY_ID,
+			1_000_000
+		));
+		// counting the donation, the 110 liquid in circulation with the seed would be backed by
+		// 1_000_011 and the deposit below would mint nothing
+		assert_eq!(LiquidStaking::backing(POOL), 11);
+
+		// the victim still mints at the seeded rate
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(102), POOL, 200, None, None));
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &102), 2000);
+		assert_eq!(
+			LiquidStaking::current_mint_rate(POOL),
+			MintRate::saturating_from_rational(10, 1)
+		);
+		// and the first minter can not redeem more than they bonded
+		assert_eq!(LiquidStaking::liquid_to_staking(POOL, 10).unwrap(), 1);
+		assert_ok!(LiquidStaking::sanity_check());
+	});
+}
+
+#[test]
+fn restaked_rewards_move_the_rate() {
+	ExtBuilder::default().build().execute_with(|| {
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		reward_pot(POOL, 210);
+
+		assert_eq!(LiquidStaking::backing(POOL), 420);
+		assert_eq!(LiquidStaking::liquid_to_staking(POOL, 2000).unwrap(), 400);
+	});
+}
+
+#[test]
+fn pots_stake_on_a_remote_chain_through_messages() {
+	ExtBuilder::default().without_genesis_liquid().remote_staking().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		let remote_ledger =
+			|| Staking::ledger(REMOTE_STASH).map(|ledger| (ledger.total, ledger.active));
+		let pot_ledger = || {
+			LiquidStaking::remote_ledger(pot_account).map(|ledger| (ledger.total, ledger.active))
+		};
+
+		// the seed was sent along with the bond, nothing is staked here
+		assert_eq!(remote_ledger(), Some((10, 10)));
+		assert_eq!(pot_ledger(), Some((10, 10)));
+		assert_eq!(Staking::ledger(pot_account), None);
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &pot_account), 0);
+
+		start_active_era(1);
+		report_remote_staking(POOL);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_eq!(remote_ledger(), Some((210, 210)));
+		assert_eq!(pot_ledger(), Some((210, 210)));
+		assert_eq!(Currencies::free_balance(LIQUID_CURRENCY_ID, &101), 2000);
+
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 51, 300));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		let targets = MockStakingBackend::nominations(&pot_account).unwrap().targets;
+		assert!(targets.contains(&51));
+		assert_eq!(Staking::nominators(REMOTE_STASH).unwrap().targets.into_inner(), targets);
+
+		// rewards on the remote chain move the rate once reported
+		assert_ok!(Currencies::deposit(STAKING_CURRENCY_ID, &REMOTE_STASH, 210));
+		assert_ok!(Staking::bond_extra(Origin::signed(REMOTE_STASH), 210));
+		assert_eq!(LiquidStaking::backing(POOL), 210);
+		report_remote_staking(POOL);
+		System::assert_last_event(mock::Event::LiquidStaking(crate::Event::PotLedgerUpdated {
+			pool_id: POOL,
+			bonded: 420,
+			active: 420,
+			unclaimed: 0,
+		}));
+		assert_eq!(LiquidStaking::current_mint_rate(POOL), MintRate::saturating_from_integer(5));
+
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_eq!(remote_ledger(), Some((420, 220)));
+		assert_eq!(pot_ledger(), Some((420, 220)));
+
+		// the bonding duration is counted in the eras of the remote chain
+		start_active_era(4);
+		assert_err!(
+			LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0),
+			Error::<Test>::UnbondingWaitNotComplete
+		);
+		report_remote_staking(POOL);
+		assert_ok!(LiquidStaking::withdraw_unbonded(Origin::signed(101), POOL, 0));
+		assert_eq!(Currencies::free_balance(STAKING_CURRENCY_ID, &101), 1000);
+		assert_eq!(remote_ledger(), Some((220, 220)));
+		assert_eq!(pot_ledger(), Some((220, 220)));
+		assert!(LiquidStaking::remote_ledger(pot_account).unwrap().unlocking.is_empty());
+		assert_ok!(LiquidStaking::sanity_check());
+	});
+}
+
+#[test]
+fn remote_reports_wait_for_the_messages_sent() {
+	ExtBuilder::default().without_genesis_liquid().remote_staking().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		let pot_ledger = || {
+			LiquidStaking::remote_ledger(pot_account).map(|ledger| (ledger.total, ledger.active))
+		};
+		start_active_era(1);
+		report_remote_staking(POOL);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+
+		RelayHeld::set(true);
+		assert_ok!(LiquidStaking::request_unbond(Origin::signed(101), POOL, 1000, None, None));
+		assert_eq!(pot_ledger(), Some((210, 110)));
+		let backing = LiquidStaking::backing(POOL);
+
+		// the remote chain reports before it processes the unbonding
+		start_active_era(2);
+		report_remote_staking(POOL);
+		assert_eq!(Staking::ledger(REMOTE_STASH).map(|ledger| ledger.active), Some(210));
+		assert_eq!(LiquidStaking::remote_era(), Some(2));
+		assert_eq!(pot_ledger(), Some((210, 110)));
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0).unwrap().staking_amount, 100);
+		assert_eq!(LiquidStaking::backing(POOL), backing);
+
+		deliver_held_messages();
+		report_remote_staking(POOL);
+		assert_eq!(pot_ledger(), Some((210, 110)));
+		assert_eq!(LiquidStaking::unbonding_requests(POOL, 0).unwrap().staking_amount, 100);
+		assert_ok!(LiquidStaking::sanity_check());
+	});
+}
+
+#[test]
+fn remote_validators_are_known_from_reports() {
+	ExtBuilder::default().without_genesis_liquid().remote_staking().build().execute_with(|| {
+		let pot_account = LiquidStaking::pot_account(POOL);
+		let targets = || MockStakingBackend::nominations(&pot_account).map(|n| n.targets);
+		// validators of this chain are not validators of the remote one
+		assert_err!(
+			LiquidStaking::add_validator(Origin::root(), 31),
+			Error::<Test>::NotAValidator
+		);
+
+		start_active_era(1);
+		report_remote_staking(POOL);
+		assert_ok!(LiquidStaking::bond_and_mint(Origin::signed(101), POOL, 200, None, None));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 51, 300));
+		assert_ok!(LiquidStaking::vote(Origin::signed(101), POOL, 61, 200));
+		assert_ok!(LiquidStaking::apply_votes(Origin::root(), POOL));
+		assert_eq!(targets(), Some(vec![51, 61]));
+		assert!(LiquidStaking::validator_score(51).is_some());
+
+		// the nominations stand while the remote chain reports nothing against them
+		run_to_block(System::block_number() + 1);
+		assert_eq!(targets(), Some(vec![51, 61]));
+
+		// slashes are dated in the eras of the remote chain
+		start_active_era(2);
+		let slashed = ValidatorStatus { last_slash_era: Some(2), ..Default::default() };
+		report_remote_validator(61, Some(slashed));
+		run_to_block(System::block_number() + 1);
+		assert_eq!(targets(), Some(vec![51]));
+		assert_eq!(LiquidStaking::approved_validators(61), None);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::ValidatorAutoRemoved {
+			validator: 61,
+			reason: RemovalReason::Slashed,
+		}));
+
+		report_remote_validator(51, None);
+		run_to_block(System::block_number() + 1);
+		assert_eq!(targets(), None);
+		System::assert_has_event(mock::Event::LiquidStaking(crate::Event::NominationRemoved {
+			pool_id: POOL,
+			validator: 51,
+			reason: RemovalReason::Chilled,
+		}));
 	});
 }
 
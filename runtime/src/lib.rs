@@ -423,6 +423,8 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	// the liquid currency minted before pools, `primitives::LIQUID_CURRENCY_ID`
+	pallet_liquid_staking::migrations::v1::MigrateToV1<Runtime, ConstU32<2>>,
 >;
 
 #[cfg(feature = "runtime-benchmarks")]